@@ -8,14 +8,14 @@ const HEIGHT: usize = 520;
 fn main() {
     // Framebuffer initialization
     let mut pixels: Vec<u32> = vec!(0; WIDTH * HEIGHT);
-    let mut fb = Framebuffer {width: WIDTH, height: HEIGHT, pixels: &mut pixels};
+    let mut fb = Framebuffer::new(WIDTH, HEIGHT, &mut pixels);
     
     // User bitmaps generating / loading
     let image:  Vec<u32> =  { vec![0xffffffff; 100] };
     let image2:  Vec<u32> =  { vec![0x0000ff00; 2500] };
     let image3:  Vec<u32> =  { vec![0x0000ff00; 100] };
     let path = "resources/test-image.png";
-    let png = from_png_file(&path, PixelFormat::Zrgb).unwrap();
+    let png = from_png_file(path, PixelFormat::Zrgb).unwrap();
 
     // Test mask
     let test_mask = vec![true, false, true, false, true, false, true, false, true, false,
@@ -31,11 +31,12 @@ fn main() {
     let mask = Mask::Bits(&test_mask);
 
     // Bitmaps structs stored in a Vec (could be a hashmap or whatever you want), to give more easily ownership to other functions
-    let mut bitmaps = Vec::new();
-    bitmaps.push(Bitmap {w: 10, h: 10, x: 0, y: 0, pixels: &image});
-    bitmaps.push(Bitmap{w: png.0, h: png.1, x: 0, y: 320, pixels: &png.2});
-    bitmaps.push(Bitmap {w: 50, h: 50, x: 590, y: 470, pixels: &image2});
-    bitmaps.push(Bitmap {w: 10, h: 10, x: 630, y: 460, pixels: &image3});
+    let mut bitmaps = vec![
+        Bitmap::new(10, 10, 0, 0, &image),
+        Bitmap::new(png.0, png.1, 0, 320, &png.2),
+        Bitmap::new(50, 50, 590, 470, &image2),
+        Bitmap::new(10, 10, 630, 460, &image3),
+    ];
 
     let mut window = Window::new(
         "Test - ESC to exit",
@@ -63,16 +64,16 @@ fn main() {
 
         // We unwrap here as we want this code to exit if it fails. Real applications may want to handle this in a different way
         window
-            .update_with_buffer(&fb.pixels, WIDTH, HEIGHT)
+            .update_with_buffer(fb.pixels, WIDTH, HEIGHT)
             .unwrap();
     }
 }
 
 // For testing : moves a 10x10 square and prints a 4x4 pixel at the center of the screen
-fn move_square(mut fb: &mut Framebuffer, bitmaps: &mut Vec<Bitmap>) {
+fn move_square(fb: &mut Framebuffer, bitmaps: &mut [Bitmap]) {
     // We just clear the animated part of the screen
     fb.clear_area(640, 10, 0, 0, 0).unwrap();
 
-    bitmaps[0].blit(&mut fb);
-    if bitmaps[0].x < WIDTH as isize - 10 { bitmaps[0].x = bitmaps[0].x+3; }
+    bitmaps[0].blit(fb);
+    if bitmaps[0].x < WIDTH as isize - 10 { bitmaps[0].x += 3; }
 }
@@ -8,13 +8,13 @@ const HEIGHT: usize = 480;
 fn main() {
     // Framebuffer initialization
     let mut pixels: Vec<u32> = vec!(0; WIDTH * HEIGHT);
-    let mut fb = Framebuffer {width: WIDTH, height: HEIGHT, pixels: &mut pixels};
+    let mut fb = Framebuffer::new(WIDTH, HEIGHT, &mut pixels);
     
     // Pixel data
     let image:  Vec<u32> =  { vec![0xffffffff; 100] };
 
     // Bitmap creation
-    let mut bitmap = Bitmap {w: 10, h: 10, x: 0, y: 0, pixels: &image};
+    let mut bitmap = Bitmap::new(10, 10, 0, 0, &image);
     
     let mut window = Window::new(
         "Test - ESC to exit",
@@ -36,16 +36,16 @@ fn main() {
 
         // We unwrap here as we want this code to exit if it fails. Real applications may want to handle this in a different way
         window
-            .update_with_buffer(&fb.pixels, WIDTH, HEIGHT)
+            .update_with_buffer(fb.pixels, WIDTH, HEIGHT)
             .unwrap();
     }
 }
 
 // For testing : moves a 10x10 square and prints a 4x4 pixel at the center of the screen
-fn move_square(mut fb: &mut Framebuffer, mut bitmap: &mut Bitmap) {
+fn move_square(fb: &mut Framebuffer, bitmap: &mut Bitmap) {
     // We just clear the animated part of the screen
     fb.clear_area(640, 10, 0, 0, 0).unwrap();
 
-    bitmap.blit(&mut fb);
-    if bitmap.x < WIDTH as isize - 10 { bitmap.x = bitmap.x+3; }
+    bitmap.blit(fb);
+    if bitmap.x < WIDTH as isize - 10 { bitmap.x += 3; }
 }
@@ -0,0 +1,153 @@
+//! X11 Bitmap Distribution Format (BDF) parsing
+
+use crate::{BitmapFont, FontError, GlyphMetrics};
+use std::collections::HashMap;
+
+/// Largest width and height of the font and glyph bounding boxes
+const MAX_SIZE: i32 = 1024;
+
+/// Integer fields of a line, after its keyword
+fn numbers(line: &str, count: usize, n: usize) -> Result<Vec<i32>, FontError> {
+    let values: Vec<i32> = line.split_whitespace().skip(1).map(|v| v.parse()).collect::<Result<_, _>>().map_err(|_| FontError::Syntax(n))?;
+    if values.len() < count {
+        return Err(FontError::Syntax(n));
+    }
+    Ok(values)
+}
+
+impl BitmapFont {
+    /// Parses an X11 BDF font. Glyphs are placed in cells of the font bounding box size, on a common baseline,
+    /// with their advance widths, and characters are mapped by their encoding (Unicode or ISO 8859-1 fonts).
+    pub fn from_bdf(text: &str) -> Result<BitmapFont, FontError> {
+        if !text.trim_start().starts_with("STARTFONT") {
+            return Err(FontError::UnknownFormat);
+        }
+        let mut lines = text.lines().enumerate().map(|(i, l)| (i + 1, l.trim()));
+        // Font bounding box: size and offset of its bottom left corner from the origin
+        let (mut w, mut h, mut fx, mut fy) = (0, 0, 0, 0);
+        let mut data = Vec::new();
+        let mut map = HashMap::new();
+        let mut metrics = Vec::new();
+        let mut row_bytes = 0;
+        let mut count = 0;
+        while let Some((n, line)) = lines.next() {
+            let keyword = line.split_whitespace().next().unwrap_or("");
+            match keyword {
+                "FONTBOUNDINGBOX" => {
+                    let v = numbers(line, 4, n)?;
+                    if !(0..=MAX_SIZE).contains(&v[0]) || !(0..=MAX_SIZE).contains(&v[1]) || v[1].checked_add(v[3]).is_none() {
+                        return Err(FontError::Syntax(n));
+                    }
+                    (w, h, fx, fy) = (v[0] as usize, v[1] as usize, v[2], v[3]);
+                    row_bytes = w.div_ceil(8);
+                }
+                "STARTCHAR" => {
+                    if w == 0 || h == 0 {
+                        return Err(FontError::UnknownFormat);
+                    }
+                    let mut encoding = -1;
+                    let mut advance = w as isize;
+                    // Glyph bounding box
+                    let (mut bw, mut bh, mut bx, mut by) = (0, 0, 0, 0);
+                    let mut cell = vec![0u8; row_bytes * h];
+                    loop {
+                        let (n, line) = lines.next().ok_or(FontError::Truncated)?;
+                        match line.split_whitespace().next().unwrap_or("") {
+                            "ENCODING" => encoding = numbers(line, 1, n)?[0],
+                            "DWIDTH" => advance = numbers(line, 1, n)?[0] as isize,
+                            "BBX" => {
+                                let v = numbers(line, 4, n)?;
+                                if !(0..=MAX_SIZE).contains(&v[0]) || !(0..=MAX_SIZE).contains(&v[1]) {
+                                    return Err(FontError::Syntax(n));
+                                }
+                                (bw, bh, bx, by) = (v[0], v[1], v[2], v[3]);
+                            }
+                            "BITMAP" => {
+                                // Top row of the glyph in the cell, its rows and columns not overflowing either
+                                let top = bh.checked_add(by).and_then(|b| (h as i32 + fy).checked_sub(b));
+                                let left = bx.checked_sub(fx);
+                                let (top, left) = match (top, left) {
+                                    (Some(t), Some(l)) if t.checked_add(bh).is_some() && l.checked_add(bw).is_some() => (t, l),
+                                    _ => return Err(FontError::Syntax(n))
+                                };
+                                for row in 0..bh {
+                                    let (n, hex) = lines.next().ok_or(FontError::Truncated)?;
+                                    if !hex.is_ascii() {
+                                        return Err(FontError::Syntax(n));
+                                    }
+                                    let bytes = (0..hex.len() / 2)
+                                        .map(|i| u8::from_str_radix(&hex[2 * i..2 * i + 2], 16))
+                                        .collect::<Result<Vec<u8>, _>>()
+                                        .map_err(|_| FontError::Syntax(n))?;
+                                    for col in 0..bw {
+                                        let set = bytes.get(col as usize / 8).is_some_and(|b| b & (0x80 >> (col % 8)) != 0);
+                                        let (x, y) = (left + col, top + row);
+                                        if set && x >= 0 && y >= 0 && (x as usize) < w && (y as usize) < h {
+                                            cell[y as usize * row_bytes + x as usize / 8] |= 0x80 >> (x % 8);
+                                        }
+                                    }
+                                }
+                            }
+                            "ENDCHAR" => break,
+                            _ => ()
+                        }
+                    }
+                    if let Some(c) = Some(encoding).filter(|e| *e >= 0).and_then(|e| char::from_u32(e as u32)) {
+                        map.insert(c, count);
+                        data.extend_from_slice(&cell);
+                        metrics.push(GlyphMetrics { advance, bearing: fx as isize });
+                        count += 1;
+                    }
+                }
+                "ENDFONT" => break,
+                _ => ()
+            }
+        }
+        let mut font = BitmapFont::from_glyphs(w, h, data, map);
+        font.set_metrics(metrics, (h as i32 + fy).max(0) as usize);
+        Ok(font)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bdf() {
+        let text = "STARTFONT 2.1
+FONT -test-
+FONTBOUNDINGBOX 4 4 0 -1
+CHARS 2
+STARTCHAR period
+ENCODING 46
+DWIDTH 2 0
+BBX 1 1 1 0
+BITMAP
+80
+ENDCHAR
+STARTCHAR uni2191
+ENCODING 8593
+BBX 3 3 0 0
+BITMAP
+40
+E0
+40
+ENDCHAR
+ENDFONT
+";
+        let font = BitmapFont::from_bdf(text).unwrap();
+        assert_eq!(font.glyph_size(), (4, 4));
+        assert_eq!(font.glyph_pixels('.', 1, 0).unwrap(), vec![0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(font.glyph_pixels('↑', 1, 0).unwrap(), vec![0, 1, 0, 0, 1, 1, 1, 0, 0, 1, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(font.baseline(), 3);
+        assert_eq!(font.glyph_metrics('.'), Some(GlyphMetrics { advance: 2, bearing: 0 }));
+        assert_eq!(font.text_width(".↑"), 6);
+        assert_eq!(BitmapFont::from_bdf("STARTFONT 2.1\nFONTBOUNDINGBOX 4 x 0 0\n"), Err(FontError::Syntax(2)));
+        assert_eq!(BitmapFont::from_bdf("STARTFONT 2.1\nFONTBOUNDINGBOX 99999 99999 0 0\n"), Err(FontError::Syntax(2)));
+        let text = "STARTFONT 2.1\nFONTBOUNDINGBOX 4 4 0 0\nSTARTCHAR a\nBBX 1 1 0 2147483647\nBITMAP\n";
+        assert_eq!(BitmapFont::from_bdf(text), Err(FontError::Syntax(5)));
+        let text = "STARTFONT 2.1\nFONTBOUNDINGBOX 4 4 0 0\nSTARTCHAR a\nBBX 2000000000 1 0 0\nBITMAP\n00\nENDCHAR\n";
+        assert_eq!(BitmapFont::from_bdf(text), Err(FontError::Syntax(4)));
+    }
+}
@@ -0,0 +1,139 @@
+//! Scanline flood and boundary fills
+
+use crate::Framebuffer;
+
+/// Neighbours a flood fill spreads to
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Connectivity {
+    /// Horizontal and vertical neighbours
+    Four,
+    /// Horizontal, vertical and diagonal neighbours
+    Eight,
+}
+
+/// Are two colors within a tolerance, channel by channel
+fn close(a: u32, b: u32, tolerance: u8) -> bool {
+    (0..4).all(|i| {
+        let (a, b) = ((a >> (i * 8)) & 0xff, (b >> (i * 8)) & 0xff);
+        a.max(b) - a.min(b) <= tolerance as u32
+    })
+}
+
+impl Framebuffer<'_> {
+    /// Fills the area around a pixel whose colors are within a tolerance of its color (0 for an exact match),
+    /// clipped to the framebuffer. Spans of pixels are filled from a stack, without recursion.
+    pub fn flood_fill(&mut self, x: isize, y: isize, color: u32, connectivity: Connectivity, tolerance: u8) {
+        let (bx0, by0, bx1, by1) = self.bounds();
+        if x < bx0 as isize || x >= bx1 as isize || y < by0 as isize || y >= by1 as isize {
+            return;
+        }
+        let target = self.pixels[self.index(x as usize, y as usize)];
+        self.fill_area(x as usize, y as usize, color, connectivity, |p| close(p, target, tolerance));
+    }
+
+    /// Fills the area around a pixel up to pixels of a border color, clipped to the framebuffer.
+    /// Nothing is filled if the pixel itself has the border color.
+    pub fn boundary_fill(&mut self, x: isize, y: isize, color: u32, border: u32, connectivity: Connectivity) {
+        let (bx0, by0, bx1, by1) = self.bounds();
+        if x < bx0 as isize || x >= bx1 as isize || y < by0 as isize || y >= by1 as isize {
+            return;
+        }
+        self.fill_area(x as usize, y as usize, color, connectivity, |p| p != border);
+    }
+
+    /// Fills the connected pixels around (x, y), in the clip bounds, whose colors match a predicate
+    fn fill_area<P: Fn(u32) -> bool>(&mut self, x: usize, y: usize, color: u32, connectivity: Connectivity, inside: P) {
+        let (bx0, by0, bx1, by1) = self.bounds();
+        let mut visited = vec![false; (bx1 - bx0) * (by1 - by0)];
+        let index = |x: usize, y: usize| (y - by0) * (bx1 - bx0) + x - bx0;
+        let matches = |fb: &Framebuffer, visited: &[bool], x: usize, y: usize| {
+            !visited[index(x, y)] && inside(fb.pixels[fb.index(x, y)])
+        };
+        let mut stack = vec![(x, y)];
+        while let Some((x, y)) = stack.pop() {
+            if !matches(self, &visited, x, y) {
+                continue;
+            }
+            // Widest span of matching pixels on the row
+            let (mut l, mut r) = (x, x + 1);
+            while l > bx0 && matches(self, &visited, l - 1, y) {
+                l -= 1;
+            }
+            while r < bx1 && matches(self, &visited, r, y) {
+                r += 1;
+            }
+            for x in l..r {
+                visited[index(x, y)] = true;
+            }
+            self.fill_clipped((l, y, r, y + 1), color);
+            // Runs of matching pixels above and below, one seed each
+            let (sl, sr) = match connectivity {
+                Connectivity::Four => (l, r),
+                Connectivity::Eight => (l.saturating_sub(1).max(bx0), (r + 1).min(bx1)),
+            };
+            let rows = [(y > by0).then(|| y - 1), (y + 1 < by1).then(|| y + 1)];
+            for ny in rows.iter().flatten() {
+                let mut in_run = false;
+                for nx in sl..sr {
+                    let m = matches(self, &visited, nx, *ny);
+                    if m && !in_run {
+                        stack.push((nx, *ny));
+                    }
+                    in_run = m;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn connectivity() {
+        let image = vec![
+            0, 0, 1, 0,
+            0, 0, 1, 0,
+            1, 1, 0, 0,
+            0, 0, 0, 1,
+        ];
+        let mut pixels = image.clone();
+        let mut fb = Framebuffer::new(4, 4, &mut pixels);
+        fb.flood_fill(0, 0, 2, Connectivity::Four, 0);
+        assert_eq!(pixels, vec![2, 2, 1, 0, 2, 2, 1, 0, 1, 1, 0, 0, 0, 0, 0, 1]);
+        let mut pixels = image;
+        let mut fb = Framebuffer::new(4, 4, &mut pixels);
+        fb.flood_fill(0, 0, 2, Connectivity::Eight, 0);
+        assert_eq!(pixels, vec![2, 2, 1, 2, 2, 2, 1, 2, 1, 1, 2, 2, 2, 2, 2, 1]);
+    }
+
+    #[test]
+    fn tolerance() {
+        let mut pixels = vec![0x101010, 0x141010, 0x202020, 0x121212];
+        let mut fb = Framebuffer::new(4, 1, &mut pixels);
+        fb.push_clip(0, 0, 3, 1);
+        fb.flood_fill(0, 0, 0x101010, Connectivity::Four, 4);
+        assert_eq!(pixels, vec![0x101010, 0x101010, 0x202020, 0x121212]);
+    }
+
+    #[test]
+    fn boundary() {
+        // Ring of 1s around a 2 pixel hole containing other colors
+        let image = vec![
+            0, 1, 1, 1,
+            1, 3, 4, 1,
+            0, 1, 1, 0,
+        ];
+        let mut pixels = image.clone();
+        let mut fb = Framebuffer::new(4, 3, &mut pixels);
+        fb.boundary_fill(1, 1, 5, 1, Connectivity::Four);
+        assert_eq!(pixels, vec![0, 1, 1, 1, 1, 5, 5, 1, 0, 1, 1, 0]);
+        // Leaks through the ring's diagonal gaps
+        let mut pixels = image;
+        let mut fb = Framebuffer::new(4, 3, &mut pixels);
+        fb.boundary_fill(1, 1, 5, 1, Connectivity::Eight);
+        fb.boundary_fill(1, 0, 6, 1, Connectivity::Eight);
+        assert_eq!(pixels, vec![5, 1, 1, 1, 1, 5, 5, 1, 5, 1, 1, 5]);
+    }
+}
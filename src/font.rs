@@ -0,0 +1,296 @@
+//! Bitmap fonts
+
+use crate::Framebuffer;
+use std::collections::HashMap;
+use std::fmt;
+
+/// Font file parsing errors
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum FontError {
+    /// Not a font of the expected format
+    UnknownFormat,
+    /// Data ending before the end of the font
+    Truncated,
+    /// Malformed BDF line (line number)
+    Syntax(usize),
+}
+
+impl fmt::Display for FontError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FontError::UnknownFormat => f.write_str("Unknown font format"),
+            FontError::Truncated => f.write_str("Truncated font data"),
+            FontError::Syntax(line) => write!(f, "Syntax error at line {}", line),
+        }
+    }
+}
+
+/// Horizontal metrics of a glyph, in pixels
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct GlyphMetrics {
+    /// Distance from the glyph's origin to the next glyph's origin
+    pub advance: isize,
+    /// Distance from the glyph's origin to the left edge of its cell
+    pub bearing: isize,
+}
+
+/// Monochrome bitmap font, glyphs being stored as 1 bit per pixel rows
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct BitmapFont {
+    // Glyph size in pixels
+    w: usize,
+    h: usize,
+    // Character of the first glyph
+    first: char,
+    // Bytes per glyph row, rows being padded to whole bytes
+    row_bytes: usize,
+    // Glyph rows, most significant bit first
+    data: Vec<u8>,
+    // Glyph indices of the characters, used instead of the first character if not empty
+    map: HashMap<char, usize>,
+    // Per glyph metrics, monospaced font if empty
+    metrics: Vec<GlyphMetrics>,
+    // Distance from the top of the cells to the baseline
+    baseline: usize,
+    // Advance adjustments of character pairs
+    kerning: HashMap<(char, char), isize>,
+}
+
+impl BitmapFont {
+    /// Font from a raw font ROM dump: consecutive w x h glyphs starting at the first character,
+    /// rows being padded to whole bytes, most significant bit first (ie. 8x8 fonts: 8 bytes per glyph)
+    pub fn from_rom(rom: &[u8], w: usize, h: usize, first: char) -> BitmapFont {
+        let row_bytes = w.div_ceil(8);
+        let glyph_bytes = (row_bytes * h).max(1);
+        let data = rom[..rom.len() - rom.len() % glyph_bytes].to_vec();
+        BitmapFont { w, h, first, row_bytes, data, map: HashMap::new(), metrics: Vec::new(), baseline: h, kerning: HashMap::new() }
+    }
+
+    /// Font from packed glyph rows, with the glyph indices of the characters
+    pub(crate) fn from_glyphs(w: usize, h: usize, data: Vec<u8>, map: HashMap<char, usize>) -> BitmapFont {
+        BitmapFont { map, ..BitmapFont::from_rom(&data, w, h, '\0') }
+    }
+
+    /// Sets the metrics of all the glyphs, and the distance from the top of the cells to the baseline
+    pub(crate) fn set_metrics(&mut self, metrics: Vec<GlyphMetrics>, baseline: usize) {
+        self.metrics = metrics;
+        self.baseline = baseline;
+    }
+
+    /// Proportional font from a monospaced one: glyphs are cropped to their set pixels, followed by a spacing.
+    /// Empty glyphs (ie. space) advance by half the glyph width.
+    pub fn proportional(mut self, spacing: usize) -> BitmapFont {
+        self.metrics = (0..self.len())
+            .map(|g| match self.ink(g) {
+                Some((x0, _, x1, _)) => GlyphMetrics { advance: (x1 - x0 + spacing) as isize, bearing: -(x0 as isize) },
+                None => GlyphMetrics { advance: (self.w / 2) as isize, bearing: 0 },
+            })
+            .collect();
+        self
+    }
+
+    /// Metrics of the glyph of a character
+    pub fn glyph_metrics(&self, c: char) -> Option<GlyphMetrics> {
+        self.glyph(c).map(|g| self.metrics_of(g))
+    }
+
+    /// Changes the metrics of the glyph of a character
+    pub fn set_glyph_metrics(&mut self, c: char, metrics: GlyphMetrics) {
+        if let Some(g) = self.glyph(c) {
+            if self.metrics.is_empty() {
+                self.metrics = (0..self.len()).map(|g| self.metrics_of(g)).collect();
+            }
+            self.metrics[g] = metrics;
+        }
+    }
+
+    /// Distance from the top of a line to the baseline
+    pub fn baseline(&self) -> usize {
+        self.baseline
+    }
+
+    /// Changes the distance from the top of a line to the baseline (the glyph height by default)
+    pub fn set_baseline(&mut self, baseline: usize) {
+        self.baseline = baseline;
+    }
+
+    /// Adjusts the advance between two characters (negative to bring them closer)
+    pub fn set_kerning(&mut self, left: char, right: char, adjustment: isize) {
+        self.kerning.insert((left, right), adjustment);
+    }
+
+    /// Advance adjustment between two characters
+    pub fn kerning(&self, left: char, right: char) -> isize {
+        self.kerning.get(&(left, right)).copied().unwrap_or(0)
+    }
+
+    /// Glyph width and height in pixels
+    pub fn glyph_size(&self) -> (usize, usize) {
+        (self.w, self.h)
+    }
+
+    /// Number of glyphs
+    pub fn len(&self) -> usize {
+        self.data.len() / (self.row_bytes * self.h).max(1)
+    }
+
+    /// Is the font empty
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Glyph index of a character
+    fn glyph(&self, c: char) -> Option<usize> {
+        if !self.map.is_empty() {
+            return self.map.get(&c).copied();
+        }
+        let i = (c as u32).checked_sub(self.first as u32)? as usize;
+        if i < self.len() { Some(i) } else { None }
+    }
+
+    /// Metrics of a glyph
+    fn metrics_of(&self, glyph: usize) -> GlyphMetrics {
+        self.metrics.get(glyph).copied().unwrap_or(GlyphMetrics { advance: self.w as isize, bearing: 0 })
+    }
+
+    /// Bounds (x0, y0, x1, y1) of the set pixels of a glyph, end excluded
+    fn ink(&self, glyph: usize) -> Option<(usize, usize, usize, usize)> {
+        let mut bounds: Option<(usize, usize, usize, usize)> = None;
+        for y in 0..self.h {
+            for x in (0..self.w).filter(|x| self.bit(glyph, *x, y)) {
+                bounds = Some(match bounds {
+                    Some((x0, y0, x1, y1)) => (x0.min(x), y0.min(y), x1.max(x + 1), y1.max(y + 1)),
+                    None => (x, y, x + 1, y + 1)
+                });
+            }
+        }
+        bounds
+    }
+
+    /// Calls a function with the glyph, origin and advance of each character of a string, '\n' starting a new line
+    fn layout<F: FnMut(Option<usize>, isize, isize, isize)>(&self, text: &str, mut f: F) {
+        let (mut x, mut y) = (0, 0);
+        let mut previous = None;
+        for c in text.chars() {
+            if c == '\n' {
+                x = 0;
+                y += self.h as isize;
+                previous = None;
+                continue;
+            }
+            if let Some(p) = previous {
+                x += self.kerning(p, c);
+            }
+            let glyph = self.glyph(c);
+            let advance = glyph.map_or(self.w as isize, |g| self.metrics_of(g).advance);
+            f(glyph, x, y, advance);
+            x += advance;
+            previous = Some(c);
+        }
+    }
+
+    /// Width in pixels of the longest line of a string, from the glyphs' advances and kerning
+    pub fn text_width(&self, text: &str) -> isize {
+        let mut width = 0;
+        self.layout(text, |_, x, _, advance| width = width.max(x + advance));
+        width
+    }
+
+    /// Bounding box (x, y, w, h) of the pixels set by drawing a string at (0, 0), (0, 0, 0, 0) if none
+    pub fn measure_text(&self, text: &str) -> (isize, isize, usize, usize) {
+        let mut bounds: Option<(isize, isize, isize, isize)> = None;
+        self.layout(text, |glyph, x, y, _| {
+            if let Some((ix0, iy0, ix1, iy1)) = glyph.and_then(|g| self.ink(g)) {
+                let cx = x + self.metrics_of(glyph.unwrap()).bearing;
+                let (gx0, gy0, gx1, gy1) = (cx + ix0 as isize, y + iy0 as isize, cx + ix1 as isize, y + iy1 as isize);
+                bounds = Some(match bounds {
+                    Some((x0, y0, x1, y1)) => (x0.min(gx0), y0.min(gy0), x1.max(gx1), y1.max(gy1)),
+                    None => (gx0, gy0, gx1, gy1)
+                });
+            }
+        });
+        bounds.map_or((0, 0, 0, 0), |(x0, y0, x1, y1)| (x0, y0, (x1 - x0) as usize, (y1 - y0) as usize))
+    }
+
+    /// Is a pixel of a glyph set
+    fn bit(&self, glyph: usize, x: usize, y: usize) -> bool {
+        self.data[(glyph * self.h + y) * self.row_bytes + x / 8] & (0x80 >> (x % 8)) != 0
+    }
+
+    /// Pixels of the glyph of a character, to be used with `Bitmap::new`
+    pub fn glyph_pixels(&self, c: char, fg: u32, bg: u32) -> Option<Vec<u32>> {
+        let g = self.glyph(c)?;
+        Some((0..self.w * self.h).map(|i| if self.bit(g, i % self.w, i / self.w) { fg } else { bg }).collect())
+    }
+
+    /// Draws the set pixels of a glyph cell at (x, y), clipped to the framebuffer
+    fn draw_glyph(&self, fb: &mut Framebuffer, x: isize, y: isize, glyph: usize, fg: u32) {
+        let (x0, y0, x1, y1) = match fb.clip(x, y, self.w, self.h) {
+            Some(r) => r,
+            None => return
+        };
+        for fy in y0..y1 {
+            for fx in x0..x1 {
+                if self.bit(glyph, (fx as isize - x) as usize, (fy as isize - y) as usize) {
+                    fb.pixels[fb.index(fx, fy)] = fg;
+                }
+            }
+        }
+    }
+
+    /// Draws a string with its top left corner at (x, y), clipped to the framebuffer. The advance of each character
+    /// is filled with the background color if any, and left untouched otherwise. '\n' starts a new line.
+    pub fn draw_text(&self, fb: &mut Framebuffer, x: isize, y: isize, text: &str, fg: u32, bg: Option<u32>) {
+        self.layout(text, |glyph, gx, gy, advance| {
+            if let Some(bg) = bg {
+                fb.fill_rect(x + gx, y + gy, advance.max(0) as usize, self.h, bg);
+            }
+            if let Some(g) = glyph {
+                self.draw_glyph(fb, x + gx + self.metrics_of(g).bearing, y + gy, g, fg);
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rom_font() {
+        // 3x2 glyphs 'A' and 'B', rows padded to a byte
+        let font = BitmapFont::from_rom(&[0b1010_0000, 0b0100_0000, 0b1110_0000, 0b0000_0000], 3, 2, 'A');
+        assert_eq!(font.len(), 2);
+        let mut pixels = vec![9; 16];
+        let mut fb = Framebuffer::new(8, 2, &mut pixels);
+        fb.push_clip(0, 0, 7, 2);
+        font.draw_text(&mut fb, -1, 0, "AB?", 1, Some(0));
+        assert_eq!(pixels, vec![0, 1, 1, 1, 1, 0, 0, 9, 1, 0, 0, 0, 0, 0, 0, 9]);
+    }
+
+    #[test]
+    fn transparent_background() {
+        let font = BitmapFont::from_rom(include_bytes!("../resources/FONT2_8X8.BIN"), 8, 8, ' ');
+        assert_eq!(font.len(), 96);
+        let mut pixels = vec![5; 16 * 16];
+        let mut fb = Framebuffer::new(16, 16, &mut pixels);
+        font.draw_text(&mut fb, 0, 0, " \n ", 1, None);
+        assert!(pixels.iter().all(|p| *p == 5));
+    }
+
+    #[test]
+    fn metrics() {
+        // 4x2 glyphs: 'A' two pixels wide in the middle, 'B' one pixel on the left
+        let mut font = BitmapFont::from_rom(&[0b0110_0000, 0b0110_0000, 0b1000_0000, 0b1000_0000], 4, 2, 'A').proportional(1);
+        assert_eq!(font.glyph_metrics('A'), Some(GlyphMetrics { advance: 3, bearing: -1 }));
+        assert_eq!(font.text_width("AB"), 5);
+        assert_eq!(font.measure_text("AB"), (0, 0, 4, 2));
+        font.set_kerning('A', 'B', -1);
+        assert_eq!(font.text_width("AB\nA"), 4);
+        assert_eq!(font.measure_text("AB\nA"), (0, 0, 3, 4));
+        let mut pixels = vec![0; 10];
+        let mut fb = Framebuffer::new(5, 2, &mut pixels);
+        font.draw_text(&mut fb, 1, 0, "AB", 1, Some(2));
+        assert_eq!(pixels, vec![0, 1, 1, 1, 2, 0, 1, 1, 1, 2]);
+    }
+}
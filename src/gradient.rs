@@ -0,0 +1,192 @@
+//! Linear, radial and conic gradients, and paint sources of the fills
+
+use crate::Framebuffer;
+use std::f32::consts::PI;
+
+/// Color source of the fills, giving the color of each framebuffer pixel
+pub trait Paint {
+    /// Color of the pixel at (x, y)
+    fn color(&self, x: usize, y: usize) -> u32;
+
+    /// Fills a span of pixels of a row, starting at (x, y)
+    fn fill_span(&self, span: &mut [u32], x: usize, y: usize) {
+        for (i, p) in span.iter_mut().enumerate() {
+            *p = self.color(x + i, y);
+        }
+    }
+}
+
+/// Plain color
+impl Paint for u32 {
+    fn color(&self, _x: usize, _y: usize) -> u32 {
+        *self
+    }
+
+    fn fill_span(&self, span: &mut [u32], _x: usize, _y: usize) {
+        for p in span {
+            *p = *self;
+        }
+    }
+}
+
+/// Geometry of a gradient, in pixels (pixel centers being at integer + 0.5 coordinates)
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum GradientShape {
+    /// Along the line from a start point (offset 0) to an end point (offset 1)
+    Linear { x0: f32, y0: f32, x1: f32, y1: f32 },
+    /// From a center (offset 0) to a circle (offset 1)
+    Radial { cx: f32, cy: f32, r: f32 },
+    /// Clockwise around a center, from a start angle (radians, 0 pointing right)
+    Conic { cx: f32, cy: f32, angle: f32 },
+}
+
+/// Gradient between color stops
+#[derive(Debug, PartialEq, Clone)]
+pub struct Gradient {
+    /// Geometry
+    pub shape: GradientShape,
+    /// Offsets (0.0 - 1.0) and colors, sorted by offset. Colors are padded before the first and after the last stop.
+    pub stops: Vec<(f32, u32)>,
+    /// Ordered dithering, against banding
+    pub dither: bool,
+}
+
+/// 4x4 Bayer matrix
+const BAYER: [[u8; 4]; 4] = [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+
+impl Gradient {
+    /// Gradient with the given shape and stops, sorted by offset, without dithering. Stops whose offset is not a
+    /// number are ignored.
+    pub fn new(shape: GradientShape, stops: &[(f32, u32)]) -> Gradient {
+        let mut stops: Vec<(f32, u32)> = stops.iter().copied().filter(|s| !s.0.is_nan()).collect();
+        stops.sort_by(|a, b| a.0.total_cmp(&b.0));
+        Gradient { shape, stops, dither: false }
+    }
+
+    /// Linear gradient from (x0, y0) to (x1, y1)
+    pub fn linear(x0: f32, y0: f32, x1: f32, y1: f32, stops: &[(f32, u32)]) -> Gradient {
+        Gradient::new(GradientShape::Linear { x0, y0, x1, y1 }, stops)
+    }
+
+    /// Radial gradient around (cx, cy)
+    pub fn radial(cx: f32, cy: f32, r: f32, stops: &[(f32, u32)]) -> Gradient {
+        Gradient::new(GradientShape::Radial { cx, cy, r }, stops)
+    }
+
+    /// Conic gradient around (cx, cy), starting at an angle
+    pub fn conic(cx: f32, cy: f32, angle: f32, stops: &[(f32, u32)]) -> Gradient {
+        Gradient::new(GradientShape::Conic { cx, cy, angle }, stops)
+    }
+
+    /// Offset along the gradient of a point
+    fn offset(&self, x: f32, y: f32) -> f32 {
+        match self.shape {
+            GradientShape::Linear { x0, y0, x1, y1 } => {
+                let (dx, dy) = (x1 - x0, y1 - y0);
+                let l2 = dx * dx + dy * dy;
+                if l2 > 0.0 { ((x - x0) * dx + (y - y0) * dy) / l2 } else { 0.0 }
+            }
+            GradientShape::Radial { cx, cy, r } => {
+                let d = ((x - cx) * (x - cx) + (y - cy) * (y - cy)).sqrt();
+                if r > 0.0 { d / r } else { 1.0 }
+            }
+            GradientShape::Conic { cx, cy, angle } => ((y - cy).atan2(x - cx) - angle).rem_euclid(2.0 * PI) / (2.0 * PI),
+        }
+    }
+
+    /// Channels of the color at an offset, before rounding
+    fn channels(&self, t: f32) -> [f32; 4] {
+        let channels = |c: u32| [0, 1, 2, 3].map(|i| ((c >> (i * 8)) & 0xff) as f32);
+        let i = self.stops.partition_point(|s| s.0 <= t);
+        if i == 0 || i == self.stops.len() {
+            return channels(self.stops[i.min(self.stops.len() - 1)].1);
+        }
+        let ((o0, c0), (o1, c1)) = (self.stops[i - 1], self.stops[i]);
+        let f = if o1 > o0 { (t - o0) / (o1 - o0) } else { 1.0 };
+        let (c0, c1) = (channels(c0), channels(c1));
+        [0, 1, 2, 3].map(|i| c0[i] + (c1[i] - c0[i]) * f)
+    }
+
+    /// Color at a point, without dithering
+    pub fn color_at(&self, x: f32, y: f32) -> u32 {
+        if self.stops.is_empty() {
+            return 0;
+        }
+        let c = self.channels(self.offset(x, y));
+        (0..4).fold(0, |acc, i| acc | ((c[i].round().clamp(0.0, 255.0) as u32) << (i * 8)))
+    }
+
+    /// Pixels of a w x h bitmap filled with the gradient, to be used with `Bitmap::new`
+    pub fn render(&self, w: usize, h: usize) -> Vec<u32> {
+        let mut pixels = vec![0; w * h];
+        for (y, row) in pixels.chunks_mut(w.max(1)).enumerate() {
+            self.fill_span(row, 0, y);
+        }
+        pixels
+    }
+}
+
+impl Paint for Gradient {
+    fn color(&self, x: usize, y: usize) -> u32 {
+        if self.stops.is_empty() {
+            return 0;
+        }
+        if !self.dither {
+            return self.color_at(x as f32 + 0.5, y as f32 + 0.5);
+        }
+        let c = self.channels(self.offset(x as f32 + 0.5, y as f32 + 0.5));
+        let threshold = (BAYER[y % 4][x % 4] as f32 + 0.5) / 16.0;
+        (0..4).fold(0, |acc, i| acc | (((c[i] + threshold).floor().clamp(0.0, 255.0) as u32) << (i * 8)))
+    }
+}
+
+impl Framebuffer<'_> {
+    /// Fills an already clipped rectangle with a paint source
+    pub(crate) fn paint_clipped(&mut self, (x0, y0, x1, y1): (usize, usize, usize, usize), paint: &(impl Paint + ?Sized)) {
+        for y in y0..y1 {
+            let row = self.index(x0, y);
+            paint.fill_span(&mut self.pixels[row..row + x1 - x0], x0, y);
+        }
+    }
+
+    /// Fills a rectangle with a paint source (color or gradient), clipped to the framebuffer
+    pub fn fill_rect_with(&mut self, x: isize, y: isize, w: usize, h: usize, paint: &impl Paint) {
+        if let Some(r) = self.clip(x, y, w, h) {
+            self.paint_clipped(r, paint);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::FillRule;
+
+    #[test]
+    fn gradients() {
+        let g = Gradient::linear(0.0, 0.0, 4.0, 0.0, &[(1.0, 0xff), (0.0, 0)]);
+        assert_eq!(g.render(4, 1), vec![0x20, 0x60, 0x9f, 0xdf]);
+        let g = Gradient::linear(0.0, 0.0, 4.0, 0.0, &[(1.0, 0xff), (f32::NAN, 0x80), (0.0, 0)]);
+        assert_eq!(g.stops, vec![(0.0, 0), (1.0, 0xff)]);
+        let g = Gradient::radial(0.0, 0.0, 1.0, &[(0.0, 0xff0000), (0.5, 0x00ff00), (1.0, 0x0000ff)]);
+        assert_eq!(g.color_at(0.25, 0.0), 0x808000);
+        assert_eq!(g.color_at(5.0, 5.0), 0x0000ff);
+        let g = Gradient::conic(0.0, 0.0, 0.0, &[(0.0, 0), (1.0, 0xff)]);
+        assert_eq!(g.color_at(0.0, 1.0), 0x40);
+    }
+
+    #[test]
+    fn dithering() {
+        // Halfway between two levels: half of the pixels of a 4x4 block are rounded up
+        let g = Gradient { dither: true, ..Gradient::linear(-1000.0, 0.0, 1004.0, 0.0, &[(0.0, 0x10), (1.0, 0x11)]) };
+        let mut pixels = vec![0; 16];
+        let mut fb = Framebuffer::new(4, 4, &mut pixels);
+        fb.fill_polygon_with(&[(0, 0), (4, 0), (4, 4), (0, 4)], FillRule::NonZero, &g);
+        assert_eq!(pixels.iter().filter(|p| **p == 0x11).count(), 8);
+        assert_eq!(pixels.iter().filter(|p| **p == 0x10).count(), 8);
+        let mut fb = Framebuffer::new(4, 4, &mut pixels);
+        fb.fill_rect_with(-2, -2, 3, 3, &0x20);
+        assert_eq!(pixels[0], 0x20);
+        assert_eq!(pixels.iter().filter(|p| **p == 0x20).count(), 1);
+    }
+}
@@ -0,0 +1,239 @@
+//! Text layout in a rectangle: word wrapping, alignment, line spacing and ellipsis
+
+use crate::{BitmapFont, Framebuffer};
+
+/// Fonts usable by the text layout
+pub trait TextFont {
+    /// Line height in pixels
+    fn line_height(&self) -> usize;
+    /// Width in pixels of a single line of text
+    fn text_width(&self, text: &str) -> isize;
+    /// Advance adjustment between two characters, none by default
+    fn kerning(&self, _left: char, _right: char) -> isize {
+        0
+    }
+    /// Draws a single line of text with its top left corner at (x, y), clipped to the framebuffer
+    fn draw_str(&self, fb: &mut Framebuffer, x: isize, y: isize, text: &str, color: u32);
+}
+
+impl TextFont for BitmapFont {
+    fn line_height(&self) -> usize {
+        self.glyph_size().1
+    }
+
+    fn text_width(&self, text: &str) -> isize {
+        BitmapFont::text_width(self, text)
+    }
+
+    fn kerning(&self, left: char, right: char) -> isize {
+        BitmapFont::kerning(self, left, right)
+    }
+
+    fn draw_str(&self, fb: &mut Framebuffer, x: isize, y: isize, text: &str, color: u32) {
+        self.draw_text(fb, x, y, text, color, None);
+    }
+}
+
+/// Horizontal alignment of the lines
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Align {
+    /// Lines start at the left edge
+    Left,
+    /// Lines are centered
+    Center,
+    /// Lines end at the right edge
+    Right,
+    /// Words spread over the whole width, except on the last line of a paragraph
+    Justify,
+}
+
+/// Text layout parameters
+#[derive(Debug, PartialEq, Clone)]
+pub struct TextStyle {
+    /// Horizontal alignment
+    pub align: Align,
+    /// Extra pixels between lines (may be negative)
+    pub line_spacing: isize,
+    /// Lines are broken between words to fit the width, and only on '\n' otherwise
+    pub wrap: bool,
+    /// Text appended to lines truncated because they don't fit, none if empty
+    pub ellipsis: String,
+}
+
+impl Default for TextStyle {
+    fn default() -> TextStyle {
+        TextStyle { align: Align::Left, line_spacing: 0, wrap: true, ellipsis: String::from("...") }
+    }
+}
+
+/// Laid out line: its words, and whether it ends a paragraph
+struct Line {
+    words: Vec<String>,
+    last: bool,
+}
+
+/// Splits a word wider than the width into pieces that fit, at least one character each
+fn split_word(font: &impl TextFont, word: &str, width: isize) -> Vec<String> {
+    let mut pieces = Vec::new();
+    let mut piece = String::new();
+    for c in word.chars() {
+        piece.push(c);
+        if font.text_width(&piece) > width && piece.chars().count() > 1 {
+            piece.pop();
+            pieces.push(std::mem::replace(&mut piece, c.to_string()));
+        }
+    }
+    pieces.push(piece);
+    pieces
+}
+
+/// Breaks a text into lines, wrapping words to a width
+fn break_lines(font: &impl TextFont, text: &str, width: isize, wrap: bool) -> Vec<Line> {
+    let mut lines = Vec::new();
+    for paragraph in text.split('\n') {
+        if !wrap {
+            lines.push(Line { words: paragraph.split_whitespace().map(String::from).collect(), last: true });
+            continue;
+        }
+        let space = font.text_width(" ");
+        let mut words: Vec<String> = Vec::new();
+        // Width of the line's words and the spaces between them, with the kerning around the spaces
+        let mut line_width = 0;
+        for word in paragraph.split_whitespace().flat_map(|w| split_word(font, w, width)) {
+            let word_width = font.text_width(&word);
+            let joined = words.last().map(|last| {
+                let (left, right) = (last.chars().last().unwrap_or(' '), word.chars().next().unwrap_or(' '));
+                line_width + font.kerning(left, ' ') + space + font.kerning(' ', right) + word_width
+            });
+            line_width = match joined {
+                Some(w) if w <= width => w,
+                Some(_) => {
+                    lines.push(Line { words: std::mem::take(&mut words), last: false });
+                    word_width
+                }
+                None => word_width
+            };
+            words.push(word);
+        }
+        lines.push(Line { words, last: true });
+    }
+    lines
+}
+
+/// Shortens a line until it fits the width with the ellipsis appended
+fn ellipsize(font: &impl TextFont, line: &str, ellipsis: &str, width: isize) -> String {
+    let mut text = line.trim_end().to_string();
+    while !text.is_empty() && font.text_width(&format!("{}{}", text, ellipsis)) > width {
+        text.pop();
+        text.truncate(text.trim_end().len());
+    }
+    text + ellipsis
+}
+
+impl Framebuffer<'_> {
+    /// Draws a text flowed into a rectangle, clipped to it, returning false if it had to be truncated
+    #[allow(clippy::too_many_arguments)]
+    pub fn draw_text_box(&mut self, font: &impl TextFont, x: isize, y: isize, w: usize, h: usize, text: &str, style: &TextStyle, color: u32) -> bool {
+        let width = w as isize;
+        let mut lines = break_lines(font, text, width, style.wrap);
+        let pitch = font.line_height() as isize + style.line_spacing;
+        // Lines fitting the height
+        let visible = if pitch > 0 {
+            ((h as isize + style.line_spacing).max(0) / pitch) as usize
+        } else {
+            lines.len()
+        };
+        let mut complete = true;
+        if lines.len() > visible {
+            lines.truncate(visible);
+            complete = false;
+            if let Some(line) = lines.last_mut() {
+                line.words = vec![ellipsize(font, &line.words.join(" "), &style.ellipsis, width)];
+                line.last = true;
+            }
+        }
+        self.push_clip(x, y, w, h);
+        for (i, line) in lines.iter().enumerate() {
+            let ly = y + i as isize * pitch;
+            let mut text = line.words.join(" ");
+            if font.text_width(&text) > width {
+                text = ellipsize(font, &text, &style.ellipsis, width);
+                complete = false;
+            }
+            let free = width - font.text_width(&text);
+            match style.align {
+                Align::Justify if !line.last && line.words.len() > 1 => {
+                    // Free space spread over the gaps between words
+                    let words: isize = line.words.iter().map(|w| font.text_width(w)).sum();
+                    let gaps = line.words.len() as isize - 1;
+                    let space = width - words;
+                    let mut wx = x;
+                    for (j, word) in line.words.iter().enumerate() {
+                        font.draw_str(self, wx, ly, word, color);
+                        wx += font.text_width(word) + space / gaps + if (j as isize) < space % gaps { 1 } else { 0 };
+                    }
+                }
+                Align::Center => font.draw_str(self, x + free / 2, ly, &text, color),
+                Align::Right => font.draw_str(self, x + free, ly, &text, color),
+                _ => font.draw_str(self, x, ly, &text, color),
+            }
+        }
+        self.pop_clip();
+        complete
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 1x1 font: every character but space is a single set pixel
+    fn font() -> BitmapFont {
+        let mut rom = vec![0x80; 96];
+        rom[0] = 0;
+        BitmapFont::from_rom(&rom, 1, 1, ' ')
+    }
+
+    /// Rows of a framebuffer as strings, '#' for set pixels
+    fn rows(pixels: &[u32], w: usize) -> Vec<String> {
+        pixels.chunks(w).map(|r| r.iter().map(|p| if *p == 1 { '#' } else { '.' }).collect()).collect()
+    }
+
+    #[test]
+    fn wrap_and_align() {
+        let font = font();
+        let mut pixels = vec![0; 30];
+        let mut fb = Framebuffer::new(6, 5, &mut pixels);
+        let style = TextStyle { align: Align::Right, line_spacing: 1, ..Default::default() };
+        assert!(fb.draw_text_box(&font, 0, 0, 6, 5, "ab cd efg\nh", &style, 1));
+        assert_eq!(rows(&pixels, 6), vec![".##.##", "......", "...###", "......", ".....#"]);
+        let mut pixels = vec![0; 24];
+        let mut fb = Framebuffer::new(6, 4, &mut pixels);
+        let style = TextStyle { align: Align::Justify, ..Default::default() };
+        assert!(fb.draw_text_box(&font, 0, 0, 6, 4, "a b c d e\nf g", &style, 1));
+        assert_eq!(rows(&pixels, 6), vec!["#..#.#", "#.#...", "#.#...", "......"]);
+    }
+
+    #[test]
+    fn ellipsis() {
+        let font = font();
+        let mut pixels = vec![0; 6];
+        let mut fb = Framebuffer::new(6, 1, &mut pixels);
+        let style = TextStyle { ellipsis: String::from("~"), ..Default::default() };
+        // Second line "ef" dropped, the ellipsis being appended to the last visible one
+        assert!(!fb.draw_text_box(&font, 0, 0, 6, 1, "ab cd ef", &style, 1));
+        assert_eq!(rows(&pixels, 6), vec!["##.###"]);
+        let mut pixels = vec![0; 6];
+        let mut fb = Framebuffer::new(6, 1, &mut pixels);
+        let style = TextStyle { align: Align::Center, wrap: false, ellipsis: String::from("~"), ..Default::default() };
+        assert!(!fb.draw_text_box(&font, 0, 0, 4, 1, "abc def", &style, 1));
+        assert_eq!(rows(&pixels, 6), vec!["####.."]);
+        // Kerning around the space pushing the second word to the next line
+        let mut font = font;
+        font.set_kerning('b', ' ', 1);
+        let mut pixels = vec![0; 12];
+        let mut fb = Framebuffer::new(6, 2, &mut pixels);
+        assert!(fb.draw_text_box(&font, 0, 0, 5, 2, "ab cd", &TextStyle::default(), 1));
+        assert_eq!(rows(&pixels, 6), vec!["##....", "##...."]);
+    }
+}
@@ -1,19 +1,41 @@
 //! This library performs various blitting and drawing operations on a raw 32 bits framebuffer, whatever the encoding:
 //! - Bitmap blitting / cropping
-//! - Blit a part of bitmap (ie. bitmap fonts)
-//! - Blit with a color or bits mask
-//! - Pixel plotting
+//! - Blit a part of bitmap (ie. bitmap fonts), with a mask
+//! - Blit with a color or bits mask, or with per-pixel alpha blending
+//! - Blend modes (additive, multiply, screen, subtract, XOR)
+//! - Amiga style three sources raster operations (minterms)
+//! - Scaled blitting with nearest neighbour or bilinear filtering
+//! - Rotated / affine transformed blitting
+//! - Flipped and quarter turn rotated blitting
+//! - Framebuffer to framebuffer blitting, overlapping copies (scrolling)
+//! - Framebuffers and bitmaps over borrowed slices, with row stride (padded rows)
+//! - Sub-framebuffers (viewports)
+//! - Clip rectangle stack
+//! - Pixel plotting, line drawing
+//! - Anti-aliased lines and Bézier curves
+//! - Thick polylines with dashes, caps and joins
+//! - Rectangles and rounded rectangles, filled or outlined
+//! - Circles, ellipses, arcs and pie slices
+//! - Polygon filling (even-odd and non-zero rules)
+//! - Triangle rasterization: flat, Gouraud shaded or texture mapped (affine or perspective correct)
+//! - Scanline flood fill (4 or 8-connected, exact or with a color tolerance) and boundary fill
+//! - Linear, radial and conic gradients, with color stops and optional dithering
+//! - Bitmap fonts from raw 1 bit font ROMs, text drawing
+//! - PC Screen Font (PSF1 / PSF2, with Unicode tables) and X11 BDF font parsing
+//! - Proportional fonts: glyph metrics, kerning, text measurement
+//! - Text layout in a rectangle: word wrap, alignment, line spacing, ellipsis
 //! - Optional PNG decoding feature
+//! - Optional TrueType / OpenType font feature, with anti-aliased glyphs
 //! 
 //! Example:
 //!```text
 //! // Framebuffer initialization
 //! let mut pixels: Vec<u32> = vec!(0; WIDTH * HEIGHT);
-//! let mut fb = Framebuffer {width: WIDTH, height: HEIGHT, pixels: &mut pixels};
+//! let mut fb = Framebuffer::new(WIDTH, HEIGHT, &mut pixels);
 //!
 //! // For example, you can push all the bitmaps in a single vec to give ownership of all bitmaps
 //! let mut bitmaps = Vec::new();
-//! bitmaps.push(Bitmap {w: 10, h: 10, x: 0, y: 0, pixels: &image::PIXELS});
+//! bitmaps.push(Bitmap::new(10, 10, 0, 0, &image::PIXELS));
 //!
 //! while *display loop with some display library* {
 //!     blitter_test(&mut fb, &mut bitmaps);
@@ -40,12 +62,37 @@ use std::{fmt, result::Result};
 #[cfg(feature = "png-decode")]
 use {png::DecodingError, std::fs::File};
 
+mod bdf;
+mod flood;
+mod font;
+mod gradient;
+mod layout;
+mod line;
+mod polygon;
+mod psf;
+mod shapes;
+mod stroke;
+mod transform;
+mod triangle;
+#[cfg(feature = "truetype")]
+mod truetype;
+pub use flood::Connectivity;
+pub use font::{BitmapFont, FontError, GlyphMetrics};
+pub use gradient::{Gradient, GradientShape, Paint};
+pub use layout::{Align, TextFont, TextStyle};
+pub use polygon::FillRule;
+pub use stroke::{LineCap, LineJoin, StrokeStyle};
+pub use transform::Transform;
+pub use triangle::{Shading, Vertex};
+#[cfg(feature = "truetype")]
+pub use truetype::{GlyphImage, TrueTypeFont};
+
 /// Output format of png decoding function
 #[cfg(feature = "png-decode")]
 pub enum PixelFormat {
     /// 0RGB
     Zrgb,
-    /// RGBA, STRIP_ALPHA
+    /// RGBA, alpha channel kept (0xff for RGB files)
     Rgba,
 }
 
@@ -53,12 +100,120 @@ pub enum PixelFormat {
 pub enum Mask<'a> {
     /// color mask (color acting as transparent in case of non alpha framebuffers)
     Color(u32),
-    /// Bits mask (logical AND)
-    Bits(&'a Vec<bool>),
+    /// Bits mask (logical AND), with the same layout (size and stride) as the bitmap
+    Bits(&'a [bool]),
+    /// Per-pixel alpha blending: RGBA source pixels (as decoded with `PixelFormat::Rgba`) composited over a 0RGB framebuffer
+    Alpha,
     /// No mask
     None
 }
 
+impl Mask<'_> {
+    /// Writes a source pixel to its destination if the mask allows it; c is the source pixel index
+    fn write(&self, dst: &mut u32, src: u32, c: usize, mode: BlendMode) {
+        match self {
+            Mask::Color(key) => if src != *key { *dst = mode.apply(src, *dst) },
+            Mask::Bits(b) => if b[c] { *dst = mode.apply(src, *dst) },
+            Mask::Alpha => *dst = alpha_blend(mode.apply(src >> 8, *dst), *dst, src & 0xff),
+            Mask::None => *dst = mode.apply(src, *dst)
+        }
+    }
+}
+
+/// Sampling filter used by scaled blits
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Filter {
+    /// Nearest neighbour (pixel art)
+    Nearest,
+    /// Bilinear interpolation (photos)
+    Bilinear,
+}
+
+/// Quarter turn rotation applied by oriented blits, clockwise
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum Rotation {
+    /// No rotation
+    #[default]
+    None,
+    /// 90 degrees
+    Cw90,
+    /// 180 degrees
+    Cw180,
+    /// 270 degrees
+    Cw270,
+}
+
+/// Orientation of oriented blits: flips are applied first, then the rotation
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct Orientation {
+    /// Horizontal mirroring
+    pub flip_x: bool,
+    /// Vertical mirroring
+    pub flip_y: bool,
+    /// Clockwise rotation
+    pub rotation: Rotation,
+}
+
+/// Blend mode applied per 8 bits channel when writing source pixels to the framebuffer
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum BlendMode {
+    /// Source replaces destination
+    Copy,
+    /// Saturated addition (particles, glows)
+    Add,
+    /// Multiplication, 0xff being 1.0 (shadows)
+    Multiply,
+    /// Inverted multiplication of inverted channels (lightening)
+    Screen,
+    /// Saturated subtraction of source from destination
+    Subtract,
+    /// Logical XOR (cursors, drawing twice restores the destination)
+    Xor,
+}
+
+impl BlendMode {
+    fn apply(self, src: u32, dst: u32) -> u32 {
+        match self {
+            BlendMode::Copy => src,
+            BlendMode::Xor => src ^ dst,
+            BlendMode::Add => per_channel(src, dst, |s, d| (s + d).min(0xff)),
+            BlendMode::Subtract => per_channel(src, dst, |s, d| d.saturating_sub(s)),
+            BlendMode::Multiply => per_channel(src, dst, |s, d| (s * d + 0x7f) / 0xff),
+            BlendMode::Screen => per_channel(src, dst, |s, d| 0xff - ((0xff - s) * (0xff - d) + 0x7f) / 0xff),
+        }
+    }
+}
+
+/// Applies f to each of the four 8 bits channels of two pixels
+fn per_channel<F: Fn(u32, u32) -> u32>(src: u32, dst: u32, f: F) -> u32 {
+    let mut r = 0;
+    for shift in &[0, 8, 16, 24] {
+        r |= f((src >> shift) & 0xff, (dst >> shift) & 0xff) << shift;
+    }
+    r
+}
+
+/// Minterm of the A source (mask), to be combined with logical operators
+pub const MINTERM_A: u8 = 0xf0;
+/// Minterm of the B source (bitmap)
+pub const MINTERM_B: u8 = 0xcc;
+/// Minterm of the C source (framebuffer)
+pub const MINTERM_C: u8 = 0xaa;
+
+/// Computes the bitwise boolean function of three sources described by an Amiga style minterm
+fn minterm(a: u32, b: u32, c: u32, function: u8) -> u32 {
+    let mut d = 0;
+    for i in 0..8 {
+        if function >> i & 1 == 1 {
+            let ta = if i & 4 != 0 { a } else { !a };
+            let tb = if i & 2 != 0 { b } else { !b };
+            let tc = if i & 1 != 0 { c } else { !c };
+            d |= ta & tb & tc;
+        }
+    }
+    d
+}
+
 /// To prevent buffer overflow
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub enum BlitError {
@@ -85,8 +240,10 @@ pub struct Bitmap<'a> {
     pub x: isize,
     /// Bitmap vertical position
     pub y: isize,
+    /// Distance in pixels between the starts of two rows (usually the width)
+    pub stride: usize,
     /// 32 bits pixel data
-    pub pixels: &'a Vec<u32>,
+    pub pixels: &'a [u32],
 }
 
 /// The framebuffer struct contains the buffer's width, height, and a pointer to its pixel data
@@ -95,8 +252,14 @@ pub struct Framebuffer<'a> {
     pub width: usize,
     /// Framebuffer height
     pub height: usize,
+    /// Distance in pixels between the starts of two rows (width + row padding)
+    pub stride: usize,
     /// 32 bits pixel data
-    pub pixels: &'a mut Vec<u32>,
+    pub pixels: &'a mut [u32],
+    // Clip rectangles (x0, y0, x1, y1), end excluded, each one inside the previous one
+    clip_stack: Vec<(usize, usize, usize, usize)>,
+    // Coordinates of the first pixel, beyond (0, 0) for viewports partly outside of their parent's clip rectangle
+    origin: (usize, usize),
 }
 
 struct ClippedCoords {
@@ -114,178 +277,342 @@ struct ClippedCoords {
     src_pixel_skip: usize,
 }
 
-impl Bitmap<'_> {
+impl<'a> Bitmap<'a> {
+    /// Creates a bitmap whose rows are contiguous
+    pub fn new(w: usize, h: usize, x: isize, y: isize, pixels: &'a [u32]) -> Bitmap<'a> {
+        Bitmap { w, h, x, y, stride: w, pixels }
+    }
+
+    /// Creates a bitmap whose rows start every stride pixels
+    pub fn with_stride(w: usize, h: usize, stride: usize, x: isize, y: isize, pixels: &'a [u32]) -> Bitmap<'a> {
+        Bitmap { w, h, x, y, stride, pixels }
+    }
+
     /// Copies a bitmap to the framebuffer, applying a mask
     pub fn blit_mask(&self, fb: &mut Framebuffer, mask: Mask) {
+        self.blit_blend(fb, mask, BlendMode::Copy);
+    }
+
+    /// Blends a bitmap into the framebuffer, applying a mask
+    pub fn blit_blend(&self, fb: &mut Framebuffer, mask: Mask, mode: BlendMode) {
+        let mut cr = match self.compute_clipping(fb) {
+            Some(c) => c,
+            None => return
+        };
+        for inc_y in 0..cr.y_end {
+            let row = fb.index(cr.ux, cr.uy + inc_y);
+            for inc_x in 0..cr.x_end {
+                mask.write(&mut fb.pixels[row + inc_x], self.pixels[cr.c], cr.c, mode);
+                cr.c += 1;
+            }
+            cr.c += cr.src_pixel_skip;
+        }
+    }
+
+    /// Amiga style raster operation: combines a mask (A), the bitmap (B) and the framebuffer (C) with a minterm function code.
+    /// The mask has the same layout (size and stride) as the bitmap; without mask, A is all ones.
+    /// For instance, `MINTERM_A & MINTERM_B | !MINTERM_A & MINTERM_C` is a cookie-cut blit.
+    pub fn blit_minterm(&self, fb: &mut Framebuffer, a: Option<&[u32]>, function: u8) {
         let mut cr = match self.compute_clipping(fb) {
             Some(c) => c,
             None => return
         };
         for inc_y in 0..cr.y_end {
-            let x_offset: usize = inc_y * fb.width;
-            let y_offset: usize = cr.uy * fb.width;
+            let row = fb.index(cr.ux, cr.uy + inc_y);
             for inc_x in 0..cr.x_end {
-                match mask {
-                    Mask::Color(c) => if self.pixels[cr.c] != c { fb.pixels[inc_x + x_offset + cr.ux + y_offset] = self.pixels[cr.c] },
-                    Mask::Bits(b) => if b[cr.c] { fb.pixels[inc_x + x_offset + cr.ux + y_offset] = self.pixels[cr.c] },
-                    Mask::None => fb.pixels[inc_x + x_offset + cr.ux + y_offset] = self.pixels[cr.c]
-                }
+                let dst = &mut fb.pixels[row + inc_x];
+                let src_a = a.map_or(0xffff_ffff, |a| a[cr.c]);
+                *dst = minterm(src_a, self.pixels[cr.c], *dst, function);
                 cr.c += 1;
             }
             cr.c += cr.src_pixel_skip;
         }
     }
 
+    /// Draws the bitmap stretched to a dw x dh rectangle at the bitmap's coordinates.
+    /// Color and bits masks are tested against the nearest source pixel.
+    pub fn blit_scaled(&self, fb: &mut Framebuffer, dw: usize, dh: usize, filter: Filter, mask: Mask) {
+        if self.w == 0 || self.h == 0 {
+            return;
+        }
+        let (x0, y0, x1, y1) = match fb.clip(self.x, self.y, dw, dh) {
+            Some(c) => c,
+            None => return
+        };
+        for fy in y0..y1 {
+            let dy = (fy as isize - self.y) as usize;
+            let sy = ((2 * dy + 1) * self.h) / (2 * dh);
+            for fx in x0..x1 {
+                let dx = (fx as isize - self.x) as usize;
+                let sx = ((2 * dx + 1) * self.w) / (2 * dw);
+                let c = sx + sy * self.stride;
+                let src = match filter {
+                    Filter::Nearest => self.pixels[c],
+                    Filter::Bilinear => {
+                        // 16.16 fixed point source coordinates, pixel centers on integers
+                        let u = (((2 * dx + 1) * self.w) << 16) as i64 / (2 * dw) as i64 - 0x8000;
+                        let v = (((2 * dy + 1) * self.h) << 16) as i64 / (2 * dh) as i64 - 0x8000;
+                        self.sample_bilinear(u, v)
+                    }
+                };
+                mask.write(&mut fb.pixels[fb.index(fx, fy)], src, c, BlendMode::Copy);
+            }
+        }
+    }
+
+    /// Bilinear sampling at 16.16 fixed point coordinates, clamped to the bitmap's edges
+    fn sample_bilinear(&self, u: i64, v: i64) -> u32 {
+        let clamp = |p: i64, max: usize| p.max(0).min(max as i64 - 1) as usize;
+        let (x0, y0) = (clamp(u >> 16, self.w), clamp(v >> 16, self.h));
+        let (x1, y1) = (clamp((u >> 16) + 1, self.w), clamp((v >> 16) + 1, self.h));
+        let (fx, fy) = (((u & 0xffff) >> 8) as u32, ((v & 0xffff) >> 8) as u32);
+        let lerp = |a: u32, b: u32, f: u32| per_channel(a, b, |a, b| (a * (0x100 - f) + b * f) >> 8);
+        let top = lerp(self.pixels[x0 + y0 * self.stride], self.pixels[x1 + y0 * self.stride], fx);
+        let bottom = lerp(self.pixels[x0 + y1 * self.stride], self.pixels[x1 + y1 * self.stride], fx);
+        lerp(top, bottom, fy)
+    }
+
     /// Convenience function to blit without any mask
     pub fn blit(&self, fb: &mut Framebuffer) {
         self.blit_mask(fb, Mask::None);
     }
 
-    /// Copies a portion of a bitmap to the framebuffer
-    pub fn blit_part(&self, fb: &mut Framebuffer, start_offset: usize, w: usize, h: usize) {
-        let mut c = 0 + start_offset;
-        // Temporary pixel buffer
-        let mut t_pixels = vec![0; w * h];
-        for inc_y in 0..h {
-            for inc_x in 0..w {
-                t_pixels[inc_x + inc_y * w ] = self.pixels[c];
-                c += 1;
-            }
-            c += self.w - w;
+    /// Copies a bitmap to the framebuffer, flipped and / or rotated, applying a mask
+    pub fn blit_mask_oriented(&self, fb: &mut Framebuffer, mask: Mask, o: Orientation) {
+        self.blit_region(fb, 0, 0, self.w, self.h, &mask, o);
+    }
+
+    /// Copies a portion of a bitmap to the framebuffer, flipped and / or rotated
+    pub fn blit_part_oriented(&self, fb: &mut Framebuffer, start_offset: usize, w: usize, h: usize, o: Orientation) {
+        if self.stride == 0 {
+            return;
         }
-        // Temporary Bitmap; this way we can use the generic blit function
-        let tx = self.x;
-        let ty = self.y;
-        let tw = w;
-        let th = h;
-        let t = Bitmap { x: tx, y: ty, w: tw, h: th, pixels: &t_pixels};
-        t.blit(fb);
+        self.blit_region(fb, start_offset % self.stride, start_offset / self.stride, w, h, &Mask::None, o);
     }
 
-    fn compute_clipping(&self, fb: &Framebuffer) -> Option<ClippedCoords> {
-        // Are x or y negative values ? compute cropped pixels size and convert x and y to unsigned values
-        let ux = if self.x > 0 { self.x as usize } else { 0 };
-        let uy = if self.y > 0 { self.y as usize } else { 0 };
-        let cropped_x = self.x.abs() as usize;
-        let cropped_y = self.y.abs() as usize;
-        // Need to crop the top of the bitmap
-        let r = if ux + self.w <= fb.width && uy + self.h < fb.height && self.x >= 0 && self.y < 0 && (self.y + self.h as isize) > 0 {
-            //println!("Cropping top");
-            Some(ClippedCoords {
-                x_end: self.w,
-                y_end: self.h - cropped_y,
-                src_pixel_skip: 0,
-                c: cropped_y * self.w,
-                ux: ux,
-                uy: uy,
-            })
+    /// Copies the (sx, sy, w, h) region of the bitmap at the bitmap's coordinates, reading the source
+    /// in the order given by the orientation
+    #[allow(clippy::too_many_arguments)]
+    fn blit_region(&self, fb: &mut Framebuffer, sx: usize, sy: usize, w: usize, h: usize, mask: &Mask, o: Orientation) {
+        // The region can't exceed the bitmap
+        let w = w.min(self.w.saturating_sub(sx));
+        let h = h.min(self.h.saturating_sub(sy));
+        if w == 0 || h == 0 {
+            return;
         }
-        // Need to crop the top left of the bitmap
-        else if self.x < 0 && self.y < 0 && self.x + self.w as isize > 0 && self.y + self.h as isize > 0 {
-            //println!("Cropping top left");
-            Some(ClippedCoords {
-                x_end: self.w - cropped_x,
-                y_end: self.h - cropped_y,
-                src_pixel_skip: cropped_x,
-                c: cropped_y * self.w + cropped_x,
-                ux: ux,
-                uy: uy,
-            })
+        let (dw, dh) = match o.rotation {
+            Rotation::None | Rotation::Cw180 => (w, h),
+            Rotation::Cw90 | Rotation::Cw270 => (h, w),
+        };
+        let (x0, y0, x1, y1) = match fb.clip(self.x, self.y, dw, dh) {
+            Some(c) => c,
+            None => return
+        };
+        let (w, h) = (w as isize, h as isize);
+        // Region coordinates (u, v) = (u0 + du_dx * dx + du_dy * dy, v0 + dv_dx * dx + dv_dy * dy)
+        let (mut u0, du_dx, du_dy, mut v0, dv_dx, dv_dy) = match o.rotation {
+            Rotation::None => (0, 1, 0, 0, 0, 1),
+            Rotation::Cw90 => (0, 0, 1, h - 1, -1, 0),
+            Rotation::Cw180 => (w - 1, -1, 0, h - 1, 0, -1),
+            Rotation::Cw270 => (w - 1, 0, -1, 0, 1, 0),
+        };
+        let (mut du_dx, mut du_dy, mut dv_dx, mut dv_dy) = (du_dx, du_dy, dv_dx, dv_dy);
+        if o.flip_x {
+            u0 = w - 1 - u0;
+            du_dx = -du_dx;
+            du_dy = -du_dy;
         }
-        // Need to crop the top right of the bitmap
-        else if ux + self.w > fb.width && ux < fb.width && self.y < 0 && self.y + self.h as isize > 0 {
-            //println!("Cropping top right");
-            Some(ClippedCoords {
-                x_end: fb.width - ux,
-                y_end: self.h - cropped_y,
-                src_pixel_skip: self.w - (fb.width - ux),
-                c: cropped_y * self.w,
-                ux: ux,
-                uy: uy,
-            })
+        if o.flip_y {
+            v0 = h - 1 - v0;
+            dv_dx = -dv_dx;
+            dv_dy = -dv_dy;
         }
-        // Need to crop the bottom left of the bitmap
-        else if uy + self.h > fb.height && self.x < 0 && self.x + self.w as isize > 0 && uy < fb.height {
-            //println!("Cropping bottom left");
-            Some(ClippedCoords {
-                x_end: self.w - cropped_x,
-                y_end: fb.height - uy,
-                src_pixel_skip: cropped_x,
-                c: cropped_x,
-                ux: ux,
-                uy: uy,
-            })
+        for fy in y0..y1 {
+            let dy = fy as isize - self.y;
+            for fx in x0..x1 {
+                let dx = fx as isize - self.x;
+                let u = (u0 + du_dx * dx + du_dy * dy) as usize;
+                let v = (v0 + dv_dx * dx + dv_dy * dy) as usize;
+                let c = sx + u + (sy + v) * self.stride;
+                mask.write(&mut fb.pixels[fb.index(fx, fy)], self.pixels[c], c, BlendMode::Copy);
+            }
         }
-        // Need to crop the bottom right of the bitmap
-        else if ux + self.w > fb.width && uy + self.h > fb.height && ux < fb.width && uy < fb.height {
-            //println!("Cropping bottom right");
-            Some(ClippedCoords {
-                x_end: fb.width - ux,
-                y_end: fb.height - uy,
-                src_pixel_skip: self.w - (fb.width - ux),
-                c: 0,
-                ux: ux,
-                uy: uy,
-            })
+    }
+
+    /// Copies a portion of a bitmap to the framebuffer
+    pub fn blit_part(&self, fb: &mut Framebuffer, start_offset: usize, w: usize, h: usize) {
+        if self.stride == 0 {
+            return;
         }
-        // Need to crop the bottom of the bitmap
-        else if ux + self.w < fb.width && self.x + self.w as isize > 0 && uy + self.h > fb.height && uy < fb.height {
-            //println!("Cropping bottom");
-            Some(ClippedCoords {
-                x_end: self.w,
-                y_end: fb.height - uy,
-                src_pixel_skip: 0,
-                c: 0,
-                ux: ux,
-                uy: uy,
-            })
+        self.blit_region(fb, start_offset % self.stride, start_offset / self.stride, w, h, &Mask::None, Orientation::default());
+    }
+
+    /// Copies the (sx, sy, w, h) rectangle of the bitmap to the framebuffer at the bitmap's coordinates, applying a mask.
+    /// Bits masks have the size of the whole bitmap.
+    pub fn blit_rect(&self, fb: &mut Framebuffer, sx: usize, sy: usize, w: usize, h: usize, mask: Mask) {
+        self.blit_region(fb, sx, sy, w, h, &mask, Orientation::default());
+    }
+
+    fn compute_clipping(&self, fb: &Framebuffer) -> Option<ClippedCoords> {
+        let (x0, y0, x1, y1) = fb.clip(self.x, self.y, self.w, self.h)?;
+        // Cropped pixels on the left and top of the bitmap
+        let cropped_x = (x0 as isize - self.x) as usize;
+        let cropped_y = (y0 as isize - self.y) as usize;
+        Some(ClippedCoords {
+            x_end: x1 - x0,
+            y_end: y1 - y0,
+            src_pixel_skip: self.stride - (x1 - x0),
+            c: cropped_y * self.stride + cropped_x,
+            ux: x0,
+            uy: y0,
+        })
+    }
+}
+
+/// Intersection of a rectangle with (x0, y0, x1, y1) bounds, end excluded
+fn intersect(bounds: (usize, usize, usize, usize), x: isize, y: isize, w: usize, h: usize) -> Option<(usize, usize, usize, usize)> {
+    let x0 = x.max(bounds.0 as isize);
+    let y0 = y.max(bounds.1 as isize);
+    let x1 = x.saturating_add(w as isize).min(bounds.2 as isize);
+    let y1 = y.saturating_add(h as isize).min(bounds.3 as isize);
+    if x0 >= x1 || y0 >= y1 {
+        return None;
+    }
+    Some((x0 as usize, y0 as usize, x1 as usize, y1 as usize))
+}
+
+/// Source-over compositing of a 0RGB pixel with an alpha value on a 0RGB pixel
+fn alpha_blend(src: u32, dst: u32, a: u32) -> u32 {
+    match a {
+        0 => dst,
+        0xff => src,
+        _ => per_channel(src, dst, |s, d| (s * a + d * (0xff - a) + 0x7f) / 0xff) & 0x00ff_ffff,
+    }
+}
+
+impl<'a> Framebuffer<'a> {
+    /// Creates a framebuffer whose rows are contiguous
+    pub fn new(width: usize, height: usize, pixels: &'a mut [u32]) -> Framebuffer<'a> {
+        Framebuffer { width, height, stride: width, pixels, clip_stack: Vec::new(), origin: (0, 0) }
+    }
+
+    /// Creates a framebuffer whose rows start every stride pixels (padded rows, borrowed buffers)
+    pub fn with_stride(width: usize, height: usize, stride: usize, pixels: &'a mut [u32]) -> Framebuffer<'a> {
+        Framebuffer { width, height, stride, pixels, clip_stack: Vec::new(), origin: (0, 0) }
+    }
+
+    /// Restricts every following drawing operation to a rectangle, inside the current clip rectangle
+    pub fn push_clip(&mut self, x: isize, y: isize, w: usize, h: usize) {
+        let r = self.clip(x, y, w, h).unwrap_or((0, 0, 0, 0));
+        self.clip_stack.push(r);
+    }
+
+    /// Restores the clip rectangle in use before the last push_clip
+    pub fn pop_clip(&mut self) {
+        self.clip_stack.pop();
+    }
+
+    /// Rectangle (x0, y0, x1, y1) backed by the pixels, end excluded
+    fn area(&self) -> (usize, usize, usize, usize) {
+        (self.origin.0, self.origin.1, self.width, self.height)
+    }
+
+    /// Current clip rectangle (x0, y0, x1, y1), end excluded
+    fn bounds(&self) -> (usize, usize, usize, usize) {
+        match self.clip_stack.last() {
+            Some(r) => *r,
+            None => self.area()
+        }
+    }
+
+    /// Index in the pixels of a pixel of the area
+    fn index(&self, x: usize, y: usize) -> usize {
+        x - self.origin.0 + (y - self.origin.1) * self.stride
+    }
+
+    /// Clips a rectangle to the current clip rectangle, returning its (x0, y0, x1, y1) bounds, end excluded
+    fn clip(&self, x: isize, y: isize, w: usize, h: usize) -> Option<(usize, usize, usize, usize)> {
+        intersect(self.bounds(), x, y, w, h)
+    }
+
+    /// Plots a pixel if it is inside the clip rectangle
+    fn put_pixel(&mut self, x: isize, y: isize, color: u32) {
+        let (x0, y0, x1, y1) = self.bounds();
+        if x >= x0 as isize && x < x1 as isize && y >= y0 as isize && y < y1 as isize {
+            self.pixels[self.index(x as usize, y as usize)] = color;
         }
-        // Need to crop the left of the bitmap
-        else if self.x < 0 && self.x + self.w as isize > 0 && self.y > 0 && uy < fb.height {
-            //println!("Cropping left");
-            Some(ClippedCoords {
-                x_end: self.w - cropped_x,
-                y_end: self.h,
-                src_pixel_skip: cropped_x,
-                c: cropped_x,
-                ux: ux,
-                uy: uy,
-            })
+    }
+
+    /// Blends a color into a pixel with an 8 bits coverage, if it is inside the clip rectangle
+    fn blend_pixel(&mut self, x: isize, y: isize, color: u32, coverage: u32) {
+        let (x0, y0, x1, y1) = self.bounds();
+        if coverage > 0 && x >= x0 as isize && x < x1 as isize && y >= y0 as isize && y < y1 as isize {
+            let dst = &mut self.pixels[self.index(x as usize, y as usize)];
+            *dst = per_channel(color, *dst, |s, d| (s * coverage + d * (0xff - coverage) + 0x7f) / 0xff);
         }
-        // Need to crop the right of the bitmap
-        else if ux + self.w > fb.width && self.y >= 0 && ux <= fb.width && uy + self.h < fb.height {
-            //println!("Cropping right");
-            Some(ClippedCoords {
-                x_end: fb.width - ux,
-                y_end: self.h,
-                src_pixel_skip: self.w - (fb.width - ux),
-                c: 0,
-                ux: ux,
-                uy: uy,
-            })
+    }
+
+    /// Fills an already clipped rectangle
+    fn fill_clipped(&mut self, (x0, y0, x1, y1): (usize, usize, usize, usize), color: u32) {
+        for y in y0..y1 {
+            let row = self.index(x0, y);
+            for p in &mut self.pixels[row..row + x1 - x0] {
+                *p = color;
+            }
         }
-        // Blitting outside the screen -> no need to blit anything
-        else if ux > fb.width || uy > fb.height || (self.x + self.w as isize) < 0 || (self.y + self.h as isize) < 0 {
-            //println!("Outside framebuffer");
-            None
+    }
+
+    /// Sub-framebuffer (viewport) over a rectangle of the framebuffer, clipped to the current clip rectangle.
+    /// Its coordinates are relative to the rectangle's top left corner, even if it is clipped away, and every
+    /// operation stays inside it.
+    pub fn viewport(&mut self, x: isize, y: isize, w: usize, h: usize) -> Framebuffer<'_> {
+        match self.clip(x, y, w, h) {
+            Some((x0, y0, x1, y1)) => {
+                let (start, end) = (self.index(x0, y0), self.index(x1, y1 - 1));
+                // Distances from the rectangle's corner, which may be far outside of the framebuffer
+                let offset = |a: usize, b: isize| (a as isize).wrapping_sub(b) as usize;
+                let mut vp = Framebuffer::with_stride(offset(x1, x), offset(y1, y), self.stride, &mut self.pixels[start..end]);
+                vp.origin = (offset(x0, x), offset(y0, y));
+                vp
+            }
+            None => Framebuffer::with_stride(0, 0, self.stride, &mut [])
         }
-        // No need to crop      self.x + self.w <= fb.width && self.y + self.h <= fb.height
-        else {
-            //println!("No cropping");
-            Some(ClippedCoords {
-                x_end: self.w,
-                y_end: self.h,
-                src_pixel_skip: 0,
-                c: 0,
-                ux: ux,
-                uy: uy,
-            })
+    }
+
+    /// Bitmap view of the framebuffer at the given coordinates, to blit it (or a rectangle of it) into another framebuffer
+    pub fn as_bitmap(&self, x: isize, y: isize) -> Bitmap<'_> {
+        let (x0, y0, x1, y1) = self.area();
+        Bitmap { w: x1 - x0, h: y1 - y0, x: x + x0 as isize, y: y + y0 as isize, stride: self.stride, pixels: self.pixels }
+    }
+
+    /// Copies the (sx, sy, w, h) rectangle of the framebuffer to (dx, dy), overlapping areas being handled like memmove
+    /// (ie. scrolling)
+    pub fn copy_within(&mut self, sx: isize, sy: isize, w: usize, h: usize, dx: isize, dy: isize) {
+        // Offsets in i128 as distant coordinates may be further apart than isize allows
+        let (ox, oy) = (dx as i128 - sx as i128, dy as i128 - sy as i128);
+        let (sx0, sy0, sx1, sy1) = match intersect(self.area(), sx, sy, w, h) {
+            Some(c) => c,
+            None => return
         };
-        r
+        // Destination of the visible source, clipped to the clip rectangle, none if it is beyond isize
+        let (tx, ty) = (sx0 as i128 + ox, sy0 as i128 + oy);
+        let range = isize::MIN as i128..=isize::MAX as i128;
+        if !range.contains(&tx) || !range.contains(&ty) {
+            return;
+        }
+        let (x0, y0, x1, y1) = match self.clip(tx as isize, ty as isize, sx1 - sx0, sy1 - sy0) {
+            Some(c) => c,
+            None => return
+        };
+        let src_x = (x0 as i128 - ox) as usize;
+        for i in 0..y1 - y0 {
+            // Moving down: bottom rows first so the source is not overwritten before being copied
+            let y = if oy > 0 { y1 - 1 - i } else { y0 + i };
+            let src = self.index(src_x, (y as i128 - oy) as usize);
+            self.pixels.copy_within(src..src + x1 - x0, self.index(x0, y));
+        }
     }
-}
 
-impl Framebuffer<'_> {
     /// Partial clear of the framebuffer
     pub fn clear_area(
         &mut self,
@@ -295,32 +622,29 @@ impl Framebuffer<'_> {
         y: usize,
         clear_color: u32,
     ) -> Result<(), BlitError> {
-        if ((x + w) * (y + h)) > self.pixels.len() {
+        if x.checked_add(w).is_none_or(|e| e > self.width) || y.checked_add(h).is_none_or(|e| e > self.height) {
             return Err(BlitError::BlittingBeyondBoundaries);
         };
-        for inc_y in 0..h {
-            let x_offset: usize = inc_y * self.width;
-            let y_offset: usize = y * self.width;
-            for inc_x in 0..w {
-                self.pixels[inc_x + x_offset + x + y_offset] = clear_color;
-            }
+        if let Some(r) = self.clip(x as isize, y as isize, w, h) {
+            self.fill_clipped(r, clear_color);
         }
         Ok(())
     }
 
-    /// Complete clear of the framebuffer
+    /// Complete clear of the framebuffer (of the clip rectangle if any)
     pub fn clear(&mut self, clear_color: u32) {
-        for inc_x in 0..self.width * self.height {
-            self.pixels[inc_x] = clear_color;
-        }
+        self.fill_clipped(self.bounds(), clear_color);
     }
 
     /// Drawing a pixel
     pub fn draw_pixel(&mut self, x: usize, y: usize, color: u32) -> Result<(), BlitError> {
-        if x > self.width || y > self.height {
+        if x >= self.width || y >= self.height {
             return Err(BlitError::BlittingBeyondBoundaries);
         };
-        self.pixels[x + y * self.width] = color;
+        let (x0, y0, x1, y1) = self.bounds();
+        if x >= x0 && x < x1 && y >= y0 && y < y1 {
+            self.pixels[self.index(x, y)] = color;
+        }
         Ok(())
     }
 
@@ -332,7 +656,7 @@ impl Framebuffer<'_> {
         size: usize,
         color: u32,
     ) -> Result<(), BlitError> {
-        if x > self.width - size || y > self.height - size {
+        if x.checked_add(size).is_none_or(|e| e > self.width) || y.checked_add(size).is_none_or(|e| e > self.height) {
             return Err(BlitError::BlittingBeyondBoundaries);
         };
         self.clear_area(size, size, x, y, color)?;
@@ -346,12 +670,9 @@ pub fn from_png_file(
     pngfile: &str,
     pxfmt: PixelFormat,
 ) -> Result<(usize, usize, Vec<u32>), DecodingError> {
-    let shift: u32 = match pxfmt {
-        PixelFormat::Zrgb => 0,
-        PixelFormat::Rgba => 8,
-    };
-    // The default output transformation is `Transformations::EXPAND | Transformations::STRIP_ALPHA`.
-    let decoder = png::Decoder::new(File::open(&pngfile)?);
+    // Alpha is kept so RGBA output can be used with `Mask::Alpha`
+    let mut decoder = png::Decoder::new(File::open(pngfile)?);
+    decoder.set_transformations(png::Transformations::EXPAND);
     let (info, mut reader) = decoder.read_info()?;
     let dpth = match info.color_type {
         png::ColorType::RGB => 3,
@@ -365,8 +686,13 @@ pub fn from_png_file(
     // convert buffer to u32
     let u32_buffer: Vec<u32> = buf
         .chunks(dpth)
-        .map(|v| ((v[0] as u32) << 16) | ((v[1] as u32) << 8) | v[2] as u32)
-        .map(|x| x << shift)
+        .map(|v| {
+            let rgb = ((v[0] as u32) << 16) | ((v[1] as u32) << 8) | v[2] as u32;
+            match pxfmt {
+                PixelFormat::Zrgb => rgb,
+                PixelFormat::Rgba => rgb << 8 | if dpth == 4 { v[3] as u32 } else { 0xff },
+            }
+        })
         .collect();
 
     Ok((info.width as usize, info.height as usize, u32_buffer))
@@ -374,8 +700,225 @@ pub fn from_png_file(
 
 #[cfg(test)]
 mod tests {
+    use super::*;
+
     #[test]
     fn it_works() {
         assert_eq!(2 + 2, 4);
     }
+
+    #[test]
+    fn alpha_mask() {
+        let mut pixels = vec![0x00ff_ffff; 4];
+        let mut fb = Framebuffer::new(2, 2, &mut pixels);
+        let src = vec![0x0000_00ff, 0x0000_0000, 0xff00_0080, 0x0000_ff00];
+        Bitmap::new(2, 2, 0, 0, &src).blit_mask(&mut fb, Mask::Alpha);
+        assert_eq!(pixels, vec![0x0000_0000, 0x00ff_ffff, 0x00ff_7f7f, 0x00ff_ffff]);
+    }
+
+    #[test]
+    fn blend_modes() {
+        let (src, dst) = (0x0080_40ff, 0x00c0_8010);
+        assert_eq!(BlendMode::Copy.apply(src, dst), src);
+        assert_eq!(BlendMode::Add.apply(src, dst), 0x00ff_c0ff);
+        assert_eq!(BlendMode::Subtract.apply(src, dst), 0x0040_4000);
+        assert_eq!(BlendMode::Multiply.apply(src, dst), 0x0060_2010);
+        assert_eq!(BlendMode::Screen.apply(src, dst), 0x00e0_a0ff);
+        assert_eq!(BlendMode::Xor.apply(BlendMode::Xor.apply(src, dst), dst), src);
+
+        let mut pixels = vec![0x0010_1010; 2];
+        let mut fb = Framebuffer::new(2, 1, &mut pixels);
+        let sprite = vec![0x0020_2020, 0x00ff_00ff];
+        Bitmap::new(2, 1, 0, 0, &sprite).blit_blend(&mut fb, Mask::Color(0x00ff_00ff), BlendMode::Add);
+        assert_eq!(pixels, vec![0x0030_3030, 0x0010_1010]);
+    }
+
+    #[test]
+    fn minterms() {
+        let mut pixels = vec![0x0000_00ff; 2];
+        let mut fb = Framebuffer::new(2, 1, &mut pixels);
+        let sprite = vec![0x00ff_0000; 2];
+        let cookie = vec![0xffff_ffff, 0];
+        let b = Bitmap::new(2, 1, 0, 0, &sprite);
+        b.blit_minterm(&mut fb, Some(&cookie), MINTERM_A & MINTERM_B | !MINTERM_A & MINTERM_C);
+        assert_eq!(pixels, vec![0x00ff_0000, 0x0000_00ff]);
+        let mut fb = Framebuffer::new(2, 1, &mut pixels);
+        b.blit_minterm(&mut fb, None, !MINTERM_C);
+        assert_eq!(pixels, vec![0xff00_ffff, 0xffff_ff00]);
+        assert_eq!(MINTERM_A & MINTERM_B | !MINTERM_A & MINTERM_C, 0xca);
+    }
+
+    #[test]
+    fn scaled() {
+        let mut pixels = vec![0; 8];
+        let mut fb = Framebuffer::new(4, 2, &mut pixels);
+        let src = vec![0x0000_0010, 0x0000_0030];
+        let mut b = Bitmap::new(2, 1, 0, 0, &src);
+        b.blit_scaled(&mut fb, 4, 2, Filter::Nearest, Mask::None);
+        assert_eq!(pixels, vec![0x10, 0x10, 0x30, 0x30, 0x10, 0x10, 0x30, 0x30]);
+        let mut fb = Framebuffer::new(4, 2, &mut pixels);
+        b.blit_scaled(&mut fb, 4, 1, Filter::Bilinear, Mask::None);
+        assert_eq!(&pixels[0..4], &[0x10, 0x18, 0x28, 0x30]);
+        let mut fb = Framebuffer::new(4, 2, &mut pixels);
+        b.x = -2;
+        b.y = 1;
+        b.blit_scaled(&mut fb, 8, 2, Filter::Nearest, Mask::Color(0x30));
+        assert_eq!(&pixels[4..8], &[0x10, 0x10, 0x30, 0x30]);
+    }
+
+    #[test]
+    fn oriented() {
+        let src = vec![1, 2, 3, 4, 5, 6];
+        let b = Bitmap::new(3, 2, 0, 0, &src);
+        let blit = |o: Orientation| {
+            let mut pixels = vec![0; 6];
+            let mut fb = Framebuffer::new(3, 2, &mut pixels);
+            b.blit_mask_oriented(&mut fb, Mask::None, o);
+            pixels
+        };
+        assert_eq!(blit(Orientation::default()), vec![1, 2, 3, 4, 5, 6]);
+        assert_eq!(blit(Orientation { flip_x: true, ..Default::default() }), vec![3, 2, 1, 6, 5, 4]);
+        assert_eq!(blit(Orientation { flip_y: true, ..Default::default() }), vec![4, 5, 6, 1, 2, 3]);
+        assert_eq!(blit(Orientation { rotation: Rotation::Cw180, ..Default::default() }), vec![6, 5, 4, 3, 2, 1]);
+        // 2x3 results in the top left of the 3x2 framebuffer
+        assert_eq!(&blit(Orientation { rotation: Rotation::Cw90, ..Default::default() })[0..2], &[4, 1]);
+        assert_eq!(&blit(Orientation { rotation: Rotation::Cw270, ..Default::default() })[0..2], &[3, 6]);
+
+        let mut pixels = vec![0; 4];
+        let mut fb = Framebuffer::new(2, 2, &mut pixels);
+        b.blit_part_oriented(&mut fb, 1, 2, 2, Orientation { flip_x: true, flip_y: true, rotation: Rotation::Cw90 });
+        assert_eq!(pixels, vec![3, 6, 2, 5]);
+    }
+
+    #[test]
+    fn rect() {
+        let src = vec![1, 2, 3, 4, 5, 6];
+        let mut b = Bitmap::new(3, 2, -1, 1, &src);
+        let mut pixels = vec![0; 4];
+        let mut fb = Framebuffer::new(2, 2, &mut pixels);
+        b.blit_rect(&mut fb, 1, 0, 5, 5, Mask::None);
+        assert_eq!(pixels, vec![0, 0, 3, 0]);
+        let mut fb = Framebuffer::new(2, 2, &mut pixels);
+        b.x = 0;
+        b.y = 0;
+        b.blit_rect(&mut fb, 1, 0, 2, 2, Mask::Color(5));
+        assert_eq!(pixels, vec![2, 3, 3, 6]);
+        let mut fb = Framebuffer::new(2, 2, &mut pixels);
+        b.blit_part(&mut fb, 3, 2, 1);
+        // Empty bitmap, without rows to offset into
+        let empty = Bitmap::new(0, 0, 0, 0, &[]);
+        empty.blit_part(&mut fb, 3, 2, 1);
+        empty.blit_part_oriented(&mut fb, 3, 2, 1, Orientation::default());
+        assert_eq!(pixels, vec![4, 5, 3, 6]);
+    }
+
+    #[test]
+    fn framebuffer_blit() {
+        let mut offscreen = vec![7; 4];
+        let off = Framebuffer::new(2, 2, &mut offscreen);
+        let mut pixels = vec![0; 9];
+        let mut fb = Framebuffer::new(3, 3, &mut pixels);
+        off.as_bitmap(2, 1).blit(&mut fb);
+        assert_eq!(pixels, vec![0, 0, 0, 0, 0, 7, 0, 0, 7]);
+    }
+
+    #[test]
+    fn overlapping_copy() {
+        let mut pixels: Vec<u32> = (0..9).collect();
+        let mut fb = Framebuffer::new(3, 3, &mut pixels);
+        // Scroll down and right by one pixel
+        fb.copy_within(0, 0, 3, 3, 1, 1);
+        assert_eq!(pixels, vec![0, 1, 2, 3, 0, 1, 6, 3, 4]);
+        let mut fb = Framebuffer::new(3, 3, &mut pixels);
+        // Scroll up
+        fb.copy_within(0, 1, 3, 2, 0, 0);
+        assert_eq!(pixels, vec![3, 0, 1, 6, 3, 4, 6, 3, 4]);
+        let mut fb = Framebuffer::new(3, 3, &mut pixels);
+        fb.copy_within(-1, -1, 2, 2, 1, 1);
+        fb.copy_within(isize::MIN, 0, 4, 4, isize::MAX, 0);
+        fb.copy_within(0, 0, 3, 3, isize::MAX, isize::MIN);
+        assert_eq!(pixels, vec![3, 0, 1, 6, 3, 4, 6, 3, 3]);
+    }
+
+    #[test]
+    fn stride() {
+        // 2x2 framebuffer and 1x2 bitmap, with one padding pixel per row
+        let mut pixels = vec![9; 5];
+        let mut fb = Framebuffer::with_stride(2, 2, 3, &mut pixels);
+        fb.clear(0);
+        let src = vec![1, 8, 2];
+        Bitmap::with_stride(1, 2, 2, 1, 0, &src).blit(&mut fb);
+        fb.draw_pixel(0, 1, 3).unwrap();
+        assert_eq!(pixels, vec![0, 1, 9, 3, 2]);
+    }
+
+    #[test]
+    fn viewport() {
+        let mut pixels = vec![0; 9];
+        let mut fb = Framebuffer::new(3, 3, &mut pixels);
+        {
+            let mut vp = fb.viewport(1, 1, 5, 5);
+            assert_eq!((vp.width, vp.height), (2, 2));
+            vp.clear(1);
+            let src = vec![2; 4];
+            Bitmap::new(2, 2, 1, -1, &src).blit(&mut vp);
+            vp.draw_pixel(0, 1, 3).unwrap();
+        }
+        assert_eq!(fb.viewport(-5, 0, 2, 2).width, 0);
+        assert_eq!(pixels, vec![0, 0, 0, 0, 1, 2, 0, 3, 1]);
+    }
+
+    #[test]
+    fn clipped_viewport_origin() {
+        let mut pixels = vec![0; 9];
+        let mut fb = Framebuffer::new(3, 3, &mut pixels);
+        {
+            // Partly off-screen, its (1, 1) pixel being the framebuffer's (0, 0)
+            let mut vp = fb.viewport(-1, -1, 3, 3);
+            vp.draw_pixel(1, 1, 7).unwrap();
+            vp.fill_rect(0, 0, 5, 1, 8);
+            let src = vec![2; 4];
+            Bitmap::new(2, 2, 1, 1, &src).blit(&mut vp.viewport(1, 1, 2, 2));
+        }
+        fb.push_clip(1, 1, 2, 2);
+        {
+            let mut vp = fb.viewport(0, 0, 3, 3);
+            vp.fill_rect(0, 0, 1, 1, 5);
+            vp.copy_within(1, 1, 1, 1, 2, 2);
+            let mut copy = vec![0; 9];
+            vp.as_bitmap(0, 0).blit(&mut Framebuffer::new(3, 3, &mut copy));
+            assert_eq!(copy, vec![0, 0, 0, 0, 2, 0, 0, 0, 2]);
+        }
+        assert_eq!(pixels, vec![7, 0, 0, 0, 2, 0, 0, 0, 2]);
+    }
+
+    #[test]
+    fn clear_area_bounds() {
+        let mut pixels = vec![0; 6];
+        let mut fb = Framebuffer::new(3, 2, &mut pixels);
+        assert_eq!(fb.clear_area(2, 1, 1, 1, 1), Ok(()));
+        assert_eq!(fb.clear_area(1, 3, 0, 0, 1), Err(BlitError::BlittingBeyondBoundaries));
+        assert_eq!(fb.draw_fatpixel(2, 0, 2, 1), Err(BlitError::BlittingBeyondBoundaries));
+        assert_eq!(fb.clear_area(usize::MAX, 1, 1, 0, 1), Err(BlitError::BlittingBeyondBoundaries));
+        assert_eq!(fb.draw_fatpixel(1, usize::MAX, 2, 1), Err(BlitError::BlittingBeyondBoundaries));
+        assert_eq!(pixels, vec![0, 0, 0, 0, 1, 1]);
+    }
+
+    #[test]
+    fn clip_stack() {
+        let mut pixels = vec![0; 9];
+        let mut fb = Framebuffer::new(3, 3, &mut pixels);
+        fb.push_clip(1, 0, 5, 2);
+        fb.push_clip(-1, 1, 3, 5);
+        fb.clear(1);
+        fb.pop_clip();
+        let src = vec![2; 4];
+        Bitmap::new(2, 2, 0, 0, &src).blit(&mut fb);
+        fb.draw_pixel(0, 0, 3).unwrap();
+        fb.pop_clip();
+        fb.draw_pixel(0, 2, 4).unwrap();
+        fb.push_clip(isize::MAX - 1, 0, 10, 10);
+        fb.fill_rect(isize::MAX - 1, isize::MAX - 1, 10, 10, 5);
+        assert_eq!(pixels, vec![0, 2, 0, 0, 2, 0, 4, 0, 0]);
+    }
 }
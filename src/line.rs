@@ -0,0 +1,293 @@
+//! Line and curve drawing
+
+use crate::Framebuffer;
+
+// Cohen-Sutherland outcodes
+const LEFT: u8 = 1;
+const RIGHT: u8 = 2;
+const TOP: u8 = 4;
+const BOTTOM: u8 = 8;
+
+/// Position of a point relative to the (x0, y0, x1, y1) clip rectangle, ends included
+fn outcode(x: i128, y: i128, r: (i128, i128, i128, i128)) -> u8 {
+    let mut code = 0;
+    if x < r.0 {
+        code |= LEFT;
+    } else if x > r.2 {
+        code |= RIGHT;
+    }
+    if y < r.1 {
+        code |= TOP;
+    } else if y > r.3 {
+        code |= BOTTOM;
+    }
+    code
+}
+
+/// Rounded a * b / d, the product being computed on unsigned magnitudes so that it doesn't overflow for 64 bits values
+fn mul_div_round(a: i128, b: i128, d: i128) -> i128 {
+    let (n, d_abs) = (a.unsigned_abs() * b.unsigned_abs(), d.unsigned_abs());
+    let q = (n / d_abs + if 2 * (n % d_abs) >= d_abs { 1 } else { 0 }) as i128;
+    if (a < 0) ^ (b < 0) ^ (d < 0) { -q } else { q }
+}
+
+/// Cohen-Sutherland clipping of a segment to the (x0, y0, x1, y1) rectangle, ends included
+fn clip_segment(mut a: (i128, i128), mut b: (i128, i128), r: (i128, i128, i128, i128)) -> Option<((i128, i128), (i128, i128))> {
+    let mut code_a = outcode(a.0, a.1, r);
+    let mut code_b = outcode(b.0, b.1, r);
+    loop {
+        if code_a | code_b == 0 {
+            return Some((a, b));
+        }
+        if code_a & code_b != 0 {
+            return None;
+        }
+        // Moves the outside end on the clip rectangle's edge
+        let code = if code_a != 0 { code_a } else { code_b };
+        let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+        let p = if code & TOP != 0 {
+            (a.0 + mul_div_round(dx, r.1 - a.1, dy), r.1)
+        } else if code & BOTTOM != 0 {
+            (a.0 + mul_div_round(dx, r.3 - a.1, dy), r.3)
+        } else if code & LEFT != 0 {
+            (r.0, a.1 + mul_div_round(dy, r.0 - a.0, dx))
+        } else {
+            (r.2, a.1 + mul_div_round(dy, r.2 - a.0, dx))
+        };
+        if code == code_a {
+            a = p;
+            code_a = outcode(a.0, a.1, r);
+        } else {
+            b = p;
+            code_b = outcode(b.0, b.1, r);
+        }
+    }
+}
+
+impl Framebuffer<'_> {
+    /// Draws a line between two points, which may be outside the framebuffer.
+    /// Bresenham's algorithm, started at the first visible pixel found by Cohen-Sutherland clipping.
+    pub fn draw_line(&mut self, x0: isize, y0: isize, x1: isize, y1: isize, color: u32) {
+        let (bx0, by0, bx1, by1) = self.bounds();
+        if bx0 >= bx1 || by0 >= by1 {
+            return;
+        }
+        let r = (bx0 as i128, by0 as i128, bx1 as i128 - 1, by1 as i128 - 1);
+        let (a, b) = ((x0 as i128, y0 as i128), (x1 as i128, y1 as i128));
+        let (ca, cb) = match clip_segment(a, b, r) {
+            Some(s) => s,
+            None => return
+        };
+        // Steps along the major axis, the minor axis moving when the error term overflows
+        let x_major = (b.0 - a.0).abs() >= (b.1 - a.1).abs();
+        let (major0, minor0, d_major, d_minor, ca, cb) = if x_major {
+            (a.0, a.1, b.0 - a.0, b.1 - a.1, ca.0, cb.0)
+        } else {
+            (a.1, a.0, b.1 - a.1, b.0 - a.0, ca.1, cb.1)
+        };
+        let (s_major, s_minor) = (d_major.signum(), d_minor.signum());
+        let (d_major, d_minor) = (d_major.abs(), d_minor.abs());
+        // Clipped steps range, widened by one step as clipping rounds; pixels outside are dropped by put_pixel
+        let k0 = ((ca - major0) * s_major - 1).max(0);
+        let k1 = ((cb - major0) * s_major + 1).min(d_major);
+        // Minor axis offset q = (2 * k0 * d_minor + d_major) / (2 * d_major) and error term e at step k0,
+        // from the quotient and remainder of k0 * d_minor / d_major so that nothing overflows
+        let (mut q, mut e) = if d_major > 0 {
+            let (p, d) = (k0 as u128 * d_minor as u128, d_major as u128);
+            let r = 2 * (p % d) + d;
+            ((p / d + r / (2 * d)) as i128, (r % (2 * d)) as i128)
+        } else {
+            (0, 0)
+        };
+        for k in k0..=k1 {
+            let major = major0 + s_major * k;
+            let minor = minor0 + s_minor * q;
+            if x_major {
+                self.put_pixel(major as isize, minor as isize, color);
+            } else {
+                self.put_pixel(minor as isize, major as isize, color);
+            }
+            e += 2 * d_minor;
+            if e >= 2 * d_major {
+                e -= 2 * d_major;
+                q += 1;
+            }
+        }
+    }
+}
+
+/// Fractional part
+fn fpart(x: f32) -> f32 {
+    x - x.floor()
+}
+
+/// Coverage (0.0 - 1.0) to 8 bits alpha
+fn coverage(c: f32) -> u32 {
+    (c * 255.0).round().clamp(0.0, 255.0) as u32
+}
+
+impl Framebuffer<'_> {
+    /// Draws an anti-aliased line between two points, which may be outside the framebuffer (Xiaolin Wu's algorithm).
+    /// Pixel centers are at integer + 0.5 coordinates.
+    pub fn draw_line_aa(&mut self, x0: f32, y0: f32, x1: f32, y1: f32, color: u32) {
+        // Wu's algorithm works with pixel centers on integer coordinates
+        let (mut x0, mut y0, mut x1, mut y1) = (x0 - 0.5, y0 - 0.5, x1 - 0.5, y1 - 0.5);
+        let steep = (y1 - y0).abs() > (x1 - x0).abs();
+        if steep {
+            std::mem::swap(&mut x0, &mut y0);
+            std::mem::swap(&mut x1, &mut y1);
+        }
+        if x0 > x1 {
+            std::mem::swap(&mut x0, &mut x1);
+            std::mem::swap(&mut y0, &mut y1);
+        }
+        let dx = x1 - x0;
+        let gradient = if dx == 0.0 { 1.0 } else { (y1 - y0) / dx };
+        let plot = |fb: &mut Framebuffer, x: isize, y: isize, c: f32| {
+            if steep {
+                fb.blend_pixel(y, x, color, coverage(c));
+            } else {
+                fb.blend_pixel(x, y, color, coverage(c));
+            }
+        };
+
+        // Ends
+        let x_start = x0.round();
+        let y_start = y0 + gradient * (x_start - x0);
+        let gap = 1.0 - fpart(x0 + 0.5);
+        plot(self, x_start as isize, y_start.floor() as isize, (1.0 - fpart(y_start)) * gap);
+        plot(self, x_start as isize, y_start.floor() as isize + 1, fpart(y_start) * gap);
+        let x_end = x1.round();
+        let y_end = y1 + gradient * (x_end - x1);
+        let gap = fpart(x1 + 0.5);
+        plot(self, x_end as isize, y_end.floor() as isize, (1.0 - fpart(y_end)) * gap);
+        plot(self, x_end as isize, y_end.floor() as isize + 1, fpart(y_end) * gap);
+
+        // Main loop, restricted to the visible part of the major axis
+        let (b0, b1) = {
+            let (bx0, by0, bx1, by1) = self.bounds();
+            if steep { (by0 as f32, by1 as f32) } else { (bx0 as f32, bx1 as f32) }
+        };
+        let first = (x_start + 1.0).max(b0);
+        let last = (x_end - 1.0).min(b1 - 1.0);
+        if first > last {
+            return;
+        }
+        let mut inter_y = y_start + gradient * (first - x_start);
+        for x in first as isize..=last as isize {
+            plot(self, x, inter_y.floor() as isize, 1.0 - fpart(inter_y));
+            plot(self, x, inter_y.floor() as isize + 1, fpart(inter_y));
+            inter_y += gradient;
+        }
+    }
+
+    /// Draws an anti-aliased quadratic Bézier curve
+    pub fn draw_quad_bezier_aa(&mut self, p0: (f32, f32), p1: (f32, f32), p2: (f32, f32), color: u32) {
+        let n = segments(&[p0, p1, p2]);
+        let mut prev = p0;
+        for i in 1..=n {
+            let t = i as f32 / n as f32;
+            let u = 1.0 - t;
+            let p = (
+                u * u * p0.0 + 2.0 * u * t * p1.0 + t * t * p2.0,
+                u * u * p0.1 + 2.0 * u * t * p1.1 + t * t * p2.1,
+            );
+            self.draw_line_aa(prev.0, prev.1, p.0, p.1, color);
+            prev = p;
+        }
+    }
+
+    /// Draws an anti-aliased cubic Bézier curve
+    pub fn draw_cubic_bezier_aa(&mut self, p0: (f32, f32), p1: (f32, f32), p2: (f32, f32), p3: (f32, f32), color: u32) {
+        let n = segments(&[p0, p1, p2, p3]);
+        let mut prev = p0;
+        for i in 1..=n {
+            let t = i as f32 / n as f32;
+            let u = 1.0 - t;
+            let p = (
+                u * u * u * p0.0 + 3.0 * u * u * t * p1.0 + 3.0 * u * t * t * p2.0 + t * t * t * p3.0,
+                u * u * u * p0.1 + 3.0 * u * u * t * p1.1 + 3.0 * u * t * t * p2.1 + t * t * t * p3.1,
+            );
+            self.draw_line_aa(prev.0, prev.1, p.0, p.1, color);
+            prev = p;
+        }
+    }
+}
+
+/// Number of line segments approximating a Bézier curve, from the length of its control polygon
+fn segments(points: &[(f32, f32)]) -> usize {
+    let length: f32 = points.windows(2).map(|w| ((w[1].0 - w[0].0).powi(2) + (w[1].1 - w[0].1).powi(2)).sqrt()).sum();
+    ((length / 4.0).ceil() as usize).clamp(1, 1024)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clipped_line() {
+        let mut pixels = vec![0; 16];
+        let mut fb = Framebuffer::new(4, 4, &mut pixels);
+        fb.draw_line(-2, -2, 10, 10, 1);
+        fb.draw_line(3, -100, 3, 100, 2);
+        fb.draw_line(-5, 10, -1, 0, 3);
+        assert_eq!(pixels, vec![1, 0, 0, 2, 0, 1, 0, 2, 0, 0, 1, 2, 0, 0, 0, 2]);
+    }
+
+    #[test]
+    fn bresenham() {
+        let mut pixels = vec![0; 8];
+        let mut fb = Framebuffer::new(4, 2, &mut pixels);
+        fb.push_clip(0, 0, 3, 2);
+        fb.draw_line(0, 0, 3, 1, 1);
+        assert_eq!(pixels, vec![1, 1, 0, 0, 0, 0, 1, 0]);
+        let mut fb = Framebuffer::new(4, 2, &mut pixels);
+        fb.draw_line(2, 1, 2, 1, 2);
+        fb.draw_line(-1000, 1000, 1000, -1000, 3);
+        assert_eq!(pixels, vec![3, 1, 0, 0, 0, 0, 2, 0]);
+    }
+
+    #[test]
+    fn far_away_ends() {
+        let e = isize::MAX / 2;
+        let mut pixels = vec![0; 16];
+        let mut fb = Framebuffer::new(4, 4, &mut pixels);
+        fb.draw_line(-e, -e, e, e, 1);
+        fb.draw_line(-e, 3, e, 3, 2);
+        fb.draw_line(-e, e, e, -e, 3);
+        assert_eq!(pixels, vec![3, 0, 0, 0, 0, 1, 0, 0, 0, 0, 1, 0, 2, 2, 2, 2]);
+        // Slightly steeper than the diagonal: one pixel per column
+        let mut pixels = vec![0; 16];
+        let mut fb = Framebuffer::new(4, 4, &mut pixels);
+        fb.draw_line(-e, -e, e, e + 3, 1);
+        fb.draw_line(isize::MIN, isize::MIN, isize::MAX, isize::MAX, 1);
+        for x in 0..4 {
+            assert!((0..4).any(|y| pixels[x + y * 4] == 1));
+        }
+    }
+
+    #[test]
+    fn anti_aliased_line() {
+        let mut pixels = vec![0; 12];
+        let mut fb = Framebuffer::new(4, 3, &mut pixels);
+        // Horizontal line between two rows: half coverage on both
+        fb.draw_line_aa(-10.0, 1.0, 10.0, 1.0, 0xff);
+        assert_eq!(pixels, vec![0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0, 0, 0, 0]);
+        let mut pixels = vec![0; 12];
+        let mut fb = Framebuffer::new(4, 3, &mut pixels);
+        fb.draw_line_aa(0.5, 0.5, 3.5, 0.5, 0xff);
+        assert_eq!(&pixels[0..4], &[0x80, 0xff, 0xff, 0x80]);
+        assert_eq!(&pixels[4..12], &[0; 8]);
+    }
+
+    #[test]
+    fn bezier() {
+        let mut pixels = vec![0; 100];
+        let mut fb = Framebuffer::new(10, 10, &mut pixels);
+        fb.draw_cubic_bezier_aa((0.5, 0.5), (3.0, 0.5), (6.0, 0.5), (9.5, 0.5), 0xff);
+        fb.draw_quad_bezier_aa((0.5, 9.5), (5.0, -5.0), (9.5, 9.5), 0xff00);
+        assert_eq!(pixels[5], 0xff);
+        assert!(pixels[90] & 0xff00 > 0 && pixels[99] & 0xff00 > 0);
+        assert_eq!(pixels[55], 0);
+    }
+}
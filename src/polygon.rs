@@ -0,0 +1,142 @@
+//! Scanline polygon filling
+
+use crate::{Framebuffer, Paint};
+
+/// Rule deciding which parts of concave or self-intersecting polygons are inside
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum FillRule {
+    /// Inside if a ray from the point crosses the outline an odd number of times
+    EvenOdd,
+    /// Inside if the outline winds around the point
+    NonZero,
+}
+
+/// Polygon edge, from top (y0) to bottom (y1)
+struct Edge {
+    y0: f32,
+    y1: f32,
+    // x at y0
+    x0: f32,
+    // dx / dy
+    slope: f32,
+    // +1 going down, -1 going up
+    dir: i32,
+}
+
+impl Framebuffer<'_> {
+    /// Fills a polygon given by its vertices, integer coordinates being pixel corners
+    pub fn fill_polygon(&mut self, points: &[(isize, isize)], rule: FillRule, color: u32) {
+        let contour: Vec<(f32, f32)> = points.iter().map(|p| (p.0 as f32, p.1 as f32)).collect();
+        self.fill_contours(&[contour], rule, &color);
+    }
+
+    /// Fills a polygon given by its vertices with a paint source (color or gradient)
+    pub fn fill_polygon_with(&mut self, points: &[(isize, isize)], rule: FillRule, paint: &impl Paint) {
+        let contour: Vec<(f32, f32)> = points.iter().map(|p| (p.0 as f32, p.1 as f32)).collect();
+        self.fill_contours(&[contour], rule, paint);
+    }
+
+    /// Fills a polygon given by fixed point vertices with frac_bits fractional bits (ie. 8 for 24.8)
+    pub fn fill_polygon_fixed(&mut self, points: &[(i32, i32)], frac_bits: u32, rule: FillRule, color: u32) {
+        let one = 2f32.powi(frac_bits.min(i32::MAX as u32) as i32);
+        let contour: Vec<(f32, f32)> = points.iter().map(|p| (p.0 as f32 / one, p.1 as f32 / one)).collect();
+        self.fill_contours(&[contour], rule, &color);
+    }
+
+    /// Fills one or several closed contours, a pixel being inside if its center is
+    pub(crate) fn fill_contours(&mut self, contours: &[Vec<(f32, f32)>], rule: FillRule, paint: &impl Paint) {
+        let mut edges = Vec::new();
+        for c in contours {
+            for i in 0..c.len() {
+                let (p, q) = (c[i], c[(i + 1) % c.len()]);
+                if p.1 == q.1 || !(p.0.is_finite() && p.1.is_finite() && q.0.is_finite() && q.1.is_finite()) {
+                    continue;
+                }
+                let (top, bottom, dir) = if p.1 < q.1 { (p, q, 1) } else { (q, p, -1) };
+                edges.push(Edge { y0: top.1, y1: bottom.1, x0: top.0, slope: (bottom.0 - top.0) / (bottom.1 - top.1), dir });
+            }
+        }
+        if edges.is_empty() {
+            return;
+        }
+        edges.sort_by(|a, b| a.y0.total_cmp(&b.y0));
+        let (bx0, by0, bx1, by1) = self.bounds();
+        let y_min = (edges[0].y0 - 0.5).ceil().max(by0 as f32) as usize;
+        let y_max = edges.iter().fold(f32::MIN, |m, e| m.max(e.y1));
+        let y_max = ((y_max - 0.5).ceil().max(0.0) as usize).min(by1);
+
+        let mut next = 0;
+        let mut active: Vec<&Edge> = Vec::new();
+        let mut crossings: Vec<(f32, i32)> = Vec::new();
+        for y in y_min..y_max {
+            let yc = y as f32 + 0.5;
+            while next < edges.len() && edges[next].y0 <= yc {
+                active.push(&edges[next]);
+                next += 1;
+            }
+            active.retain(|e| e.y1 > yc);
+            crossings.clear();
+            crossings.extend(active.iter().filter(|e| e.y0 <= yc).map(|e| (e.x0 + (yc - e.y0) * e.slope, e.dir)));
+            crossings.sort_by(|a, b| a.0.total_cmp(&b.0));
+            let mut winding = 0;
+            for i in 0..crossings.len() {
+                winding += crossings[i].1;
+                let inside = match rule {
+                    FillRule::NonZero => winding != 0,
+                    FillRule::EvenOdd => winding % 2 != 0,
+                };
+                if !inside || i + 1 == crossings.len() {
+                    continue;
+                }
+                // Pixels whose center is between the two crossings
+                let x0 = ((crossings[i].0 - 0.5).ceil().max(bx0 as f32) as usize).min(bx1);
+                let x1 = ((crossings[i + 1].0 - 0.5).ceil().max(0.0) as usize).min(bx1);
+                if x0 < x1 {
+                    self.paint_clipped((x0, y, x1, y + 1), paint);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn contours() {
+        let mut pixels = vec![0; 16];
+        let mut fb = Framebuffer::new(4, 4, &mut pixels);
+        // Square with a square hole, drawn in opposite directions
+        let outer = vec![(0.0, 0.0), (4.0, 0.0), (4.0, 4.0), (0.0, 4.0)];
+        let inner = vec![(1.0, 1.0), (1.0, 3.0), (3.0, 3.0), (3.0, 1.0)];
+        fb.fill_contours(&[outer, inner], FillRule::NonZero, &1);
+        assert_eq!(pixels, vec![1, 1, 1, 1, 1, 0, 0, 1, 1, 0, 0, 1, 1, 1, 1, 1]);
+    }
+
+    #[test]
+    fn fill_rules() {
+        // Pentagram: the center is inside with the non-zero rule only
+        let star = [(2, 0), (4, 5), (-1, 2), (5, 2), (0, 5)];
+        let mut pixels = vec![0; 25];
+        let mut fb = Framebuffer::new(5, 5, &mut pixels);
+        fb.fill_polygon(&star, FillRule::NonZero, 1);
+        assert_eq!(pixels[12], 1);
+        let mut pixels = vec![0; 25];
+        let mut fb = Framebuffer::new(5, 5, &mut pixels);
+        fb.fill_polygon(&star, FillRule::EvenOdd, 1);
+        assert_eq!(pixels[12], 0);
+        assert_eq!(pixels[7], 1);
+    }
+
+    #[test]
+    fn fixed_point() {
+        let mut pixels = vec![0; 4];
+        let mut fb = Framebuffer::new(2, 2, &mut pixels);
+        // Triangle (0, 0), (2.5, 0), (0, 2.5) in 24.8
+        fb.fill_polygon_fixed(&[(0, 0), (640, 0), (0, 640)], 8, FillRule::EvenOdd, 1);
+        // Sub-pixel sized with 64 fractional bits
+        fb.fill_polygon_fixed(&[(0, 0), (640, 0), (0, 640)], 64, FillRule::EvenOdd, 2);
+        assert_eq!(pixels, vec![1, 1, 1, 0]);
+    }
+}
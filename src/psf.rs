@@ -0,0 +1,157 @@
+//! PC Screen Font (PSF1 and PSF2) parsing
+
+use crate::{BitmapFont, FontError};
+use std::collections::HashMap;
+
+const PSF1_MAGIC: [u8; 2] = [0x36, 0x04];
+const PSF1_MODE512: u8 = 0x01;
+const PSF1_MODEHASTAB: u8 = 0x02;
+const PSF1_MODESEQ: u8 = 0x04;
+const PSF1_SEPARATOR: u16 = 0xffff;
+const PSF1_STARTSEQ: u16 = 0xfffe;
+
+const PSF2_MAGIC: [u8; 4] = [0x72, 0xb5, 0x4a, 0x86];
+const PSF2_HAS_UNICODE_TABLE: u32 = 0x01;
+const PSF2_SEPARATOR: u8 = 0xff;
+const PSF2_STARTSEQ: u8 = 0xfe;
+
+/// Little endian 32 bits integer at an offset
+fn u32_at(data: &[u8], offset: usize) -> Result<u32, FontError> {
+    let b = data.get(offset..offset + 4).ok_or(FontError::Truncated)?;
+    Ok(u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+}
+
+impl BitmapFont {
+    /// Parses a PC Screen Font (PSF1 or PSF2). Characters are mapped with the font's Unicode table if any,
+    /// and by glyph index otherwise.
+    pub fn from_psf(data: &[u8]) -> Result<BitmapFont, FontError> {
+        if data.starts_with(&PSF1_MAGIC) {
+            parse_psf1(data)
+        } else if data.starts_with(&PSF2_MAGIC) {
+            parse_psf2(data)
+        } else {
+            Err(FontError::UnknownFormat)
+        }
+    }
+}
+
+fn parse_psf1(data: &[u8]) -> Result<BitmapFont, FontError> {
+    let mode = *data.get(2).ok_or(FontError::Truncated)?;
+    let h = *data.get(3).ok_or(FontError::Truncated)? as usize;
+    let count = if mode & PSF1_MODE512 != 0 { 512 } else { 256 };
+    let glyphs = data.get(4..4 + count * h).ok_or(FontError::Truncated)?;
+    let mut map = HashMap::new();
+    if mode & (PSF1_MODEHASTAB | PSF1_MODESEQ) != 0 {
+        let mut entries = data[4 + count * h..].chunks_exact(2).map(|b| u16::from_le_bytes([b[0], b[1]]));
+        for glyph in 0..count {
+            // Single characters, then sequences which are ignored
+            let mut sequence = false;
+            loop {
+                match entries.next().ok_or(FontError::Truncated)? {
+                    PSF1_SEPARATOR => break,
+                    PSF1_STARTSEQ => sequence = true,
+                    u if !sequence => {
+                        if let Some(c) = char::from_u32(u as u32) {
+                            map.entry(c).or_insert(glyph);
+                        }
+                    }
+                    _ => ()
+                }
+            }
+        }
+    } else {
+        map.extend((0..count).filter_map(|i| char::from_u32(i as u32).map(|c| (c, i))));
+    }
+    Ok(BitmapFont::from_glyphs(8, h, glyphs.to_vec(), map))
+}
+
+fn parse_psf2(data: &[u8]) -> Result<BitmapFont, FontError> {
+    let header_size = u32_at(data, 8)? as usize;
+    let flags = u32_at(data, 12)?;
+    let count = u32_at(data, 16)? as usize;
+    let glyph_size = u32_at(data, 20)? as usize;
+    let h = u32_at(data, 24)? as usize;
+    let w = u32_at(data, 28)? as usize;
+    if header_size < 32 || glyph_size == 0 || glyph_size != w.div_ceil(8) * h {
+        return Err(FontError::UnknownFormat);
+    }
+    // Glyphs which don't fit in the data, count being bounded by its length from now on
+    if count > data.len().saturating_sub(header_size) / glyph_size {
+        return Err(FontError::Truncated);
+    }
+    let end = header_size + count * glyph_size;
+    let glyphs = data.get(header_size..end).ok_or(FontError::Truncated)?;
+    let mut map = HashMap::new();
+    if flags & PSF2_HAS_UNICODE_TABLE != 0 {
+        let mut table = &data[end..];
+        for glyph in 0..count {
+            let entry_end = table.iter().position(|b| *b == PSF2_SEPARATOR).ok_or(FontError::Truncated)?;
+            // UTF-8 single characters, then sequences which are ignored
+            let singles = &table[..entry_end];
+            let singles = &singles[..singles.iter().position(|b| *b == PSF2_STARTSEQ).unwrap_or(singles.len())];
+            for c in String::from_utf8_lossy(singles).chars().filter(|c| *c != char::REPLACEMENT_CHARACTER) {
+                map.entry(c).or_insert(glyph);
+            }
+            table = &table[entry_end + 1..];
+        }
+    } else {
+        map.extend((0..count).filter_map(|i| char::from_u32(i as u32).map(|c| (c, i))));
+    }
+    Ok(BitmapFont::from_glyphs(w, h, glyphs.to_vec(), map))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Framebuffer;
+
+    #[test]
+    fn psf1() {
+        // 256 glyphs of height 1, 'é' mapped to glyph 1
+        let mut data = vec![0x36, 0x04, PSF1_MODEHASTAB, 1];
+        data.extend((0..256).map(|i| if i == 1 { 0b1100_0000 } else { 0 }));
+        for i in 0..256u16 {
+            let u: u16 = if i == 1 { 'é' as u16 } else { i + 0x100 };
+            data.extend_from_slice(&u.to_le_bytes());
+            data.extend_from_slice(&PSF1_SEPARATOR.to_le_bytes());
+        }
+        let font = BitmapFont::from_psf(&data).unwrap();
+        assert_eq!(font.glyph_size(), (8, 1));
+        assert_eq!(font.glyph_pixels('é', 1, 0).unwrap(), vec![1, 1, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(font.glyph_pixels('a', 1, 0), None);
+        assert_eq!(BitmapFont::from_psf(&data[..100]), Err(FontError::Truncated));
+    }
+
+    #[test]
+    fn psf2() {
+        // Two 10x2 glyphs, the second one mapped to 'Ω' and 'Z', with a sequence
+        let mut data = PSF2_MAGIC.to_vec();
+        for v in [0u32, 32, PSF2_HAS_UNICODE_TABLE, 2, 4, 2, 10] {
+            data.extend_from_slice(&v.to_le_bytes());
+        }
+        data.extend_from_slice(&[0, 0, 0, 0, 0b1000_0000, 0b0100_0000, 0, 0]);
+        data.extend_from_slice(b"A\xff");
+        data.extend_from_slice("ΩZ".as_bytes());
+        data.push(PSF2_STARTSEQ);
+        data.extend_from_slice(b"e\xcc\x81\xff");
+        let font = BitmapFont::from_psf(&data).unwrap();
+        assert_eq!(font.len(), 2);
+        let mut pixels = vec![0; 20];
+        let mut fb = Framebuffer::new(10, 2, &mut pixels);
+        font.draw_text(&mut fb, 0, 0, "Ω", 1, None);
+        assert_eq!(pixels, vec![1, 0, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(font.glyph_pixels('Z', 1, 0), font.glyph_pixels('Ω', 1, 0));
+        assert_eq!(font.glyph_pixels('e', 1, 0), None);
+        assert_eq!(BitmapFont::from_psf(b"not a font"), Err(FontError::UnknownFormat));
+        // Empty glyphs, and too many of them
+        let header = |count: u32, glyph_size: u32, h: u32| {
+            let mut data = PSF2_MAGIC.to_vec();
+            for v in [0u32, 32, 0, count, glyph_size, h, 8] {
+                data.extend_from_slice(&v.to_le_bytes());
+            }
+            data
+        };
+        assert_eq!(BitmapFont::from_psf(&header(u32::MAX, 0, 0)), Err(FontError::UnknownFormat));
+        assert_eq!(BitmapFont::from_psf(&header(u32::MAX, 1, 1)), Err(FontError::Truncated));
+    }
+}
@@ -0,0 +1,280 @@
+//! Rectangles, rounded rectangles, circles, ellipses and arcs
+
+use crate::Framebuffer;
+use std::f32::consts::PI;
+use std::ops::Range;
+
+/// Left and right insets of a row of a rounded rectangle, radii being clamped to half the size
+fn rounded_row(w: usize, h: usize, radii: [usize; 4], row: usize) -> (usize, usize) {
+    let max = w.min(h) / 2;
+    let [tl, tr, br, bl] = [radii[0].min(max), radii[1].min(max), radii[2].min(max), radii[3].min(max)];
+    // Inset of a corner of radius r, at dy rows from the corner's straight edge
+    let inset = |r: usize, dy: usize| {
+        if dy >= r {
+            return 0;
+        }
+        // First pixel whose center is inside the corner's circle
+        let d = r as f32 - dy as f32 - 0.5;
+        (r as f32 - 0.5 - (r as f32 * r as f32 - d * d).sqrt()).ceil().max(0.0) as usize
+    };
+    let from_bottom = h - 1 - row;
+    (inset(tl, row).max(inset(bl, from_bottom)), inset(tr, row).max(inset(br, from_bottom)))
+}
+
+/// Widest x of the row y of an ellipse centered on the origin, -1 beyond it, like the midpoint algorithm: the curve
+/// is taken half a row up where it is flat, and rounded where it is steep
+fn ellipse_span(rx: usize, ry: usize, y: usize) -> isize {
+    if y > ry {
+        return -1;
+    }
+    let (rx, ry) = (rx as f64, ry as f64);
+    let x = |t: f64| match t.abs() {
+        a if a > ry => 0.0,
+        _ if ry == 0.0 => rx,
+        a => rx * (1.0 - (a / ry) * (a / ry)).max(0.0).sqrt(),
+    };
+    x(y as f64 - 0.5).floor().max((x(y as f64) + 0.5).floor()) as isize
+}
+
+/// Part of the lo..hi range inside the b0..b1 clip range
+fn clipped(lo: i128, hi: i128, b0: usize, b1: usize) -> Range<usize> {
+    let (lo, hi) = (lo.max(b0 as i128), hi.min(b1 as i128));
+    if lo < hi { lo as usize..hi as usize } else { 0..0 }
+}
+
+/// Is the (x, y) direction inside the clockwise angular range from start to end (radians, y axis pointing down)
+fn in_angle_range(x: f32, y: f32, start: f32, end: f32) -> bool {
+    let sweep = end - start;
+    if sweep >= 2.0 * PI {
+        return true;
+    }
+    let a = y.atan2(x);
+    (a - start).rem_euclid(2.0 * PI) <= sweep.rem_euclid(2.0 * PI) + 1e-4
+}
+
+impl Framebuffer<'_> {
+    /// Fills a rectangle, clipped to the framebuffer
+    pub fn fill_rect(&mut self, x: isize, y: isize, w: usize, h: usize, color: u32) {
+        if let Some(r) = self.clip(x, y, w, h) {
+            self.fill_clipped(r, color);
+        }
+    }
+
+    /// Draws the one pixel outline of a rectangle, clipped to the framebuffer
+    pub fn draw_rect(&mut self, x: isize, y: isize, w: usize, h: usize, color: u32) {
+        if w == 0 || h == 0 {
+            return;
+        }
+        self.fill_rect(x, y, w, 1, color);
+        self.fill_rect(x, y + h as isize - 1, w, 1, color);
+        self.fill_rect(x, y + 1, 1, h.saturating_sub(2), color);
+        self.fill_rect(x + w as isize - 1, y + 1, 1, h.saturating_sub(2), color);
+    }
+
+    /// Fills a rounded rectangle, radii being given for the top left, top right, bottom right and bottom left corners
+    pub fn fill_rounded_rect(&mut self, x: isize, y: isize, w: usize, h: usize, radii: [usize; 4], color: u32) {
+        // Rows inside the clip bounds only
+        let (_, y0, _, y1) = match self.clip(x, y, w, h) {
+            Some(r) => r,
+            None => return
+        };
+        for row in (y0 as isize - y) as usize..(y1 as isize - y) as usize {
+            let (l, r) = rounded_row(w, h, radii, row);
+            self.fill_rect(x + l as isize, y + row as isize, w - l - r, 1, color);
+        }
+    }
+
+    /// Draws the one pixel outline of a rounded rectangle, radii being given for the top left, top right, bottom right
+    /// and bottom left corners
+    pub fn draw_rounded_rect(&mut self, x: isize, y: isize, w: usize, h: usize, radii: [usize; 4], color: u32) {
+        if w == 0 || h == 0 {
+            return;
+        }
+        let (_, y0, _, y1) = match self.clip(x, y, w, h) {
+            Some(r) => r,
+            None => return
+        };
+        for row in (y0 as isize - y) as usize..(y1 as isize - y) as usize {
+            let (l, r) = rounded_row(w, h, radii, row);
+            let (start, end) = (l, w - r);
+            let py = y + row as isize;
+            if row == 0 || row == h - 1 {
+                self.fill_rect(x + start as isize, py, end - start, 1, color);
+                continue;
+            }
+            // Pixels of the row beyond the spans of the rows above and below, so that the outline stays connected
+            let (la, ra) = rounded_row(w, h, radii, row - 1);
+            let (lb, rb) = rounded_row(w, h, radii, row + 1);
+            let left_end = la.min(lb).max(start + 1);
+            let right_start = (w - ra.min(rb)).min(end - 1);
+            self.fill_rect(x + start as isize, py, left_end - start, 1, color);
+            self.fill_rect(x + right_start as isize, py, end - right_start, 1, color);
+        }
+    }
+}
+
+impl Framebuffer<'_> {
+    /// Draws the outline of an ellipse, clipped to the framebuffer
+    pub fn draw_ellipse(&mut self, cx: isize, cy: isize, rx: usize, ry: usize, color: u32) {
+        self.ellipse_outline(cx, cy, rx, ry, |_, _| true, color);
+    }
+
+    /// Fills an ellipse, clipped to the framebuffer
+    pub fn fill_ellipse(&mut self, cx: isize, cy: isize, rx: usize, ry: usize, color: u32) {
+        let (bx0, by0, bx1, by1) = self.bounds();
+        // Rows inside the clip bounds only
+        for py in clipped(cy as i128 - ry as i128, cy as i128 + ry as i128 + 1, by0, by1) {
+            let x = ellipse_span(rx, ry, (py as isize).abs_diff(cy)) as i128;
+            let span = clipped(cx as i128 - x, cx as i128 + x + 1, bx0, bx1);
+            self.fill_clipped((span.start, py, span.end, py + 1), color);
+        }
+    }
+
+    /// Draws the outline of a circle, clipped to the framebuffer
+    pub fn draw_circle(&mut self, cx: isize, cy: isize, r: usize, color: u32) {
+        self.draw_ellipse(cx, cy, r, r, color);
+    }
+
+    /// Fills a circle, clipped to the framebuffer
+    pub fn fill_circle(&mut self, cx: isize, cy: isize, r: usize, color: u32) {
+        self.fill_ellipse(cx, cy, r, r, color);
+    }
+
+    /// Draws an arc of circle, clockwise from the start angle to the end angle (radians, 0 pointing right)
+    pub fn draw_arc(&mut self, cx: isize, cy: isize, r: usize, start: f32, end: f32, color: u32) {
+        self.ellipse_outline(cx, cy, r, r, |x, y| in_angle_range(x as f32, y as f32, start, end), color);
+    }
+
+    /// Fills a pie slice of circle, clockwise from the start angle to the end angle (radians, 0 pointing right)
+    pub fn fill_pie(&mut self, cx: isize, cy: isize, r: usize, start: f32, end: f32, color: u32) {
+        let (bx0, by0, bx1, by1) = self.bounds();
+        for py in clipped(cy as i128 - r as i128, cy as i128 + r as i128 + 1, by0, by1) {
+            let y = py as i128 - cy as i128;
+            let x = ellipse_span(r, r, y.unsigned_abs() as usize) as i128;
+            for px in clipped(cx as i128 - x, cx as i128 + x + 1, bx0, bx1) {
+                let x = px as i128 - cx as i128;
+                if in_angle_range(x as f32, y as f32, start, end) || (x == 0 && y == 0) {
+                    self.pixels[self.index(px, py)] = color;
+                }
+            }
+        }
+    }
+
+    /// Plots the pixels of an ellipse's outline whose offsets from the center pass a filter, clipped to the
+    /// framebuffer: each row goes from the end of the next row's span, towards the center, to the end of its own
+    fn ellipse_outline<F: Fn(i128, i128) -> bool>(&mut self, cx: isize, cy: isize, rx: usize, ry: usize, filter: F, color: u32) {
+        let (bx0, by0, bx1, by1) = self.bounds();
+        for py in clipped(cy as i128 - ry as i128, cy as i128 + ry as i128 + 1, by0, by1) {
+            let y = py as i128 - cy as i128;
+            let outer = ellipse_span(rx, ry, y.unsigned_abs() as usize) as i128;
+            let inner = (ellipse_span(rx, ry, (y.unsigned_abs() as usize).saturating_add(1)) as i128 + 1).min(outer);
+            let left = clipped(cx as i128 - outer, cx as i128 - inner + 1, bx0, bx1);
+            let right = clipped(cx as i128 + inner, cx as i128 + outer + 1, bx0, bx1);
+            for px in left.chain(right) {
+                if filter(px as i128 - cx as i128, y) {
+                    self.pixels[self.index(px, py)] = color;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rects() {
+        let mut pixels = vec![0; 16];
+        let mut fb = Framebuffer::new(4, 4, &mut pixels);
+        fb.fill_rect(-2, 2, 4, 10, 1);
+        fb.draw_rect(1, -1, 3, 3, 2);
+        assert_eq!(pixels, vec![0, 2, 0, 2, 0, 2, 2, 2, 1, 1, 0, 0, 1, 1, 0, 0]);
+    }
+
+    #[test]
+    fn rounded_rects() {
+        let mut pixels = vec![0; 64];
+        let mut fb = Framebuffer::new(8, 8, &mut pixels);
+        fb.fill_rounded_rect(0, 0, 8, 8, [4, 0, 0, 2], 1);
+        let expected = vec![
+            0, 0, 1, 1, 1, 1, 1, 1,
+            0, 1, 1, 1, 1, 1, 1, 1,
+            1, 1, 1, 1, 1, 1, 1, 1,
+            1, 1, 1, 1, 1, 1, 1, 1,
+            1, 1, 1, 1, 1, 1, 1, 1,
+            1, 1, 1, 1, 1, 1, 1, 1,
+            1, 1, 1, 1, 1, 1, 1, 1,
+            0, 1, 1, 1, 1, 1, 1, 1,
+        ];
+        assert_eq!(pixels, expected);
+        let mut pixels = vec![0; 64];
+        let mut fb = Framebuffer::new(8, 8, &mut pixels);
+        fb.draw_rounded_rect(0, 0, 8, 8, [4, 0, 0, 2], 1);
+        let expected = vec![
+            0, 0, 1, 1, 1, 1, 1, 1,
+            0, 1, 0, 0, 0, 0, 0, 1,
+            1, 0, 0, 0, 0, 0, 0, 1,
+            1, 0, 0, 0, 0, 0, 0, 1,
+            1, 0, 0, 0, 0, 0, 0, 1,
+            1, 0, 0, 0, 0, 0, 0, 1,
+            1, 0, 0, 0, 0, 0, 0, 1,
+            0, 1, 1, 1, 1, 1, 1, 1,
+        ];
+        assert_eq!(pixels, expected);
+        // Empty, and mostly outside of the clip rectangle
+        let mut pixels = vec![0; 4];
+        let mut fb = Framebuffer::new(2, 2, &mut pixels);
+        fb.draw_rounded_rect(0, 0, 0, 4, [1; 4], 1);
+        fb.fill_rounded_rect(0, -(1 << 40), 2, 1 << 41, [1; 4], 1);
+        assert_eq!(pixels, vec![1; 4]);
+    }
+
+    #[test]
+    fn circles() {
+        let mut pixels = vec![0; 25];
+        let mut fb = Framebuffer::new(5, 5, &mut pixels);
+        fb.fill_circle(2, 2, 2, 1);
+        fb.draw_circle(2, 2, 2, 2);
+        let expected = vec![
+            0, 2, 2, 2, 0,
+            2, 1, 1, 1, 2,
+            2, 1, 1, 1, 2,
+            2, 1, 1, 1, 2,
+            0, 2, 2, 2, 0,
+        ];
+        assert_eq!(pixels, expected);
+        let mut fb = Framebuffer::new(5, 5, &mut pixels);
+        fb.fill_ellipse(2, 2, 2, 0, 3);
+        fb.draw_ellipse(2, 2, 0, 2, 4);
+        assert_eq!(&pixels[10..15], &[3, 3, 4, 3, 3]);
+        assert_eq!(pixels[2], 4);
+        // Radii far beyond the framebuffer, only its rows being walked
+        let mut pixels = vec![0; 16];
+        let mut fb = Framebuffer::new(4, 4, &mut pixels);
+        fb.fill_circle(0, 0, 1 << 33, 1);
+        fb.draw_circle(0, 0, (1 << 32) + 1, 2);
+        assert_eq!(pixels, vec![1; 16]);
+    }
+
+    #[test]
+    fn arcs() {
+        let mut pixels = vec![0; 25];
+        let mut fb = Framebuffer::new(5, 5, &mut pixels);
+        // Bottom right quarter
+        fb.fill_pie(2, 2, 2, 0.0, PI / 2.0, 1);
+        fb.draw_arc(2, 2, 2, PI, 1.5 * PI, 2);
+        let expected = vec![
+            0, 2, 2, 0, 0,
+            2, 0, 0, 0, 0,
+            2, 0, 1, 1, 1,
+            0, 0, 1, 1, 1,
+            0, 0, 1, 1, 0,
+        ];
+        assert_eq!(pixels, expected);
+        let mut fb = Framebuffer::new(5, 5, &mut pixels);
+        fb.fill_pie(-30000, 2, 20000, 0.0, PI, 3);
+        fb.fill_pie(2, 2, 1 << 40, PI / 2.0, PI, 3);
+        assert_eq!(&pixels[20..25], &[3, 3, 3, 1, 0]);
+    }
+}
@@ -0,0 +1,274 @@
+//! Thick lines and polylines, with dashes, caps and joins
+
+use crate::{FillRule, Framebuffer};
+use std::f32::consts::PI;
+
+/// Shape of the ends of open polylines and dashes
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum LineCap {
+    /// Ends exactly at the end points
+    Butt,
+    /// Half circle around the end points
+    Round,
+    /// Extended by half the line width
+    Square,
+}
+
+/// Shape of the corners of polylines
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum LineJoin {
+    /// Sharp corner, beveled beyond the miter limit
+    Miter,
+    /// Rounded corner
+    Round,
+    /// Cut corner
+    Bevel,
+}
+
+/// Stroke parameters of polylines
+#[derive(Debug, PartialEq, Clone)]
+pub struct StrokeStyle {
+    /// Line width in pixels
+    pub width: f32,
+    /// Ends of open polylines and dashes
+    pub cap: LineCap,
+    /// Corners
+    pub join: LineJoin,
+    /// Maximum ratio of the miter length to half the line width, before beveling
+    pub miter_limit: f32,
+    /// Alternating dash and gap lengths, solid line if empty
+    pub dashes: Vec<f32>,
+    /// Distance into the dash pattern at the start of the polyline
+    pub dash_offset: f32,
+}
+
+impl Default for StrokeStyle {
+    fn default() -> StrokeStyle {
+        StrokeStyle { width: 1.0, cap: LineCap::Butt, join: LineJoin::Miter, miter_limit: 4.0, dashes: Vec::new(), dash_offset: 0.0 }
+    }
+}
+
+type Point = (f32, f32);
+
+fn sub(a: Point, b: Point) -> Point {
+    (a.0 - b.0, a.1 - b.1)
+}
+
+fn add(a: Point, b: Point) -> Point {
+    (a.0 + b.0, a.1 + b.1)
+}
+
+fn scale(a: Point, k: f32) -> Point {
+    (a.0 * k, a.1 * k)
+}
+
+fn length(a: Point) -> f32 {
+    (a.0 * a.0 + a.1 * a.1).sqrt()
+}
+
+/// Unit vector, None for a null vector
+fn unit(a: Point) -> Option<Point> {
+    let l = length(a);
+    if l > f32::EPSILON { Some(scale(a, 1.0 / l)) } else { None }
+}
+
+/// Polygon approximating a circle
+pub(crate) fn circle_polygon(c: Point, r: f32) -> Vec<Point> {
+    let n = ((2.0 * PI * r / 2.0).ceil() as usize).clamp(8, 256);
+    (0..n)
+        .map(|i| {
+            let a = 2.0 * PI * i as f32 / n as f32;
+            (c.0 + r * a.cos(), c.1 + r * a.sin())
+        })
+        .collect()
+}
+
+/// Signed area, positive for clockwise polygons on screen (y axis pointing down)
+fn area(p: &[Point]) -> f32 {
+    (0..p.len()).map(|i| {
+        let (a, b) = (p[i], p[(i + 1) % p.len()]);
+        a.0 * b.1 - b.0 * a.1
+    }).sum::<f32>() / 2.0
+}
+
+/// Splits a polyline into its dashes
+fn dash(points: &[Point], dashes: &[f32], offset: f32) -> Vec<Vec<Point>> {
+    let period: f32 = dashes.iter().sum::<f32>() * if dashes.len() % 2 == 1 { 2.0 } else { 1.0 };
+    let pattern = |i: usize| dashes[i % dashes.len()];
+    // Position in the pattern
+    let mut index = 0;
+    let mut left = pattern(0);
+    let mut pos = if period > 0.0 { offset.rem_euclid(period) } else { 0.0 };
+    while pos > 0.0 {
+        if pos >= left {
+            pos -= left;
+            index += 1;
+            left = pattern(index);
+        } else {
+            left -= pos;
+            pos = 0.0;
+        }
+    }
+    let mut result = Vec::new();
+    let mut current = if index % 2 == 0 { vec![points[0]] } else { Vec::new() };
+    for w in points.windows(2) {
+        let (mut a, b) = (w[0], w[1]);
+        let mut seg = length(sub(b, a));
+        while seg > 0.0 {
+            let step = left.min(seg);
+            let p = add(a, scale(sub(b, a), step / seg));
+            if index % 2 == 0 {
+                current.push(p);
+            }
+            seg -= step;
+            left -= step;
+            a = p;
+            if left <= 0.0 {
+                if index % 2 == 0 {
+                    result.push(std::mem::take(&mut current));
+                } else {
+                    current.push(p);
+                }
+                index += 1;
+                left = pattern(index);
+            }
+        }
+    }
+    if index % 2 == 0 && current.len() > 1 {
+        result.push(current);
+    }
+    result
+}
+
+impl Framebuffer<'_> {
+    /// Draws a thick polyline, closed or not, with the given stroke style
+    pub fn draw_polyline(&mut self, points: &[(f32, f32)], closed: bool, style: &StrokeStyle, color: u32) {
+        if points.is_empty() || style.width <= 0.0 {
+            return;
+        }
+        let mut points = points.to_vec();
+        points.dedup();
+        let mut polygons = Vec::new();
+        if style.dashes.iter().sum::<f32>() > 0.0 && style.dashes.iter().all(|d| *d >= 0.0) {
+            if closed {
+                points.push(points[0]);
+            }
+            for d in dash(&points, &style.dashes, style.dash_offset) {
+                stroke(&d, false, style, &mut polygons);
+            }
+        } else {
+            if closed && points.len() > 1 && points[0] == points[points.len() - 1] {
+                points.pop();
+            }
+            stroke(&points, closed && points.len() > 2, style, &mut polygons);
+        }
+        // Same orientation for all the pieces, so that the non-zero rule fills their union
+        for p in polygons.iter_mut() {
+            if area(p) < 0.0 {
+                p.reverse();
+            }
+        }
+        self.fill_contours(&polygons, FillRule::NonZero, &color);
+    }
+}
+
+/// Outline polygons of a stroked polyline
+fn stroke(points: &[Point], closed: bool, style: &StrokeStyle, polygons: &mut Vec<Vec<Point>>) {
+    let hw = style.width / 2.0;
+    let n = points.len();
+    let segments = if closed { n } else { n - 1 };
+    // Single point: only round and square caps are visible
+    if segments == 0 {
+        match style.cap {
+            LineCap::Butt => (),
+            LineCap::Round => polygons.push(circle_polygon(points[0], hw)),
+            LineCap::Square => {
+                let p = points[0];
+                polygons.push(vec![(p.0 - hw, p.1 - hw), (p.0 + hw, p.1 - hw), (p.0 + hw, p.1 + hw), (p.0 - hw, p.1 + hw)]);
+            }
+        }
+        return;
+    }
+    let dir = |i: usize| unit(sub(points[(i + 1) % n], points[i])).unwrap_or((1.0, 0.0));
+    let normal = |d: Point| (-d.1 * hw, d.0 * hw);
+    for i in 0..segments {
+        let (p, q) = (points[i], points[(i + 1) % n]);
+        let nv = normal(dir(i));
+        polygons.push(vec![add(p, nv), add(q, nv), sub(q, nv), sub(p, nv)]);
+    }
+    // Joins
+    let joins = if closed { 0..n } else { 1..n - 1 };
+    for i in joins {
+        let (d1, d2) = (dir((i + n - 1) % n), dir(i));
+        let v = points[i];
+        let cross = d1.0 * d2.1 - d1.1 * d2.0;
+        if cross.abs() < f32::EPSILON && d1.0 * d2.0 + d1.1 * d2.1 > 0.0 {
+            continue;
+        }
+        // Outer side of the corner
+        let s = if cross > 0.0 { -1.0 } else { 1.0 };
+        let o1 = add(v, scale(normal(d1), s));
+        let o2 = add(v, scale(normal(d2), s));
+        match style.join {
+            LineJoin::Round => polygons.push(circle_polygon(v, hw)),
+            LineJoin::Bevel => polygons.push(vec![v, o1, o2]),
+            LineJoin::Miter => {
+                let bisector = unit(add(sub(o1, v), sub(o2, v)));
+                let cos_half = bisector.map_or(0.0, |b| (b.0 * (o1.0 - v.0) + b.1 * (o1.1 - v.1)) / hw);
+                if cos_half > f32::EPSILON && 1.0 / cos_half <= style.miter_limit {
+                    let m = add(v, scale(bisector.unwrap(), hw / cos_half));
+                    polygons.push(vec![v, o1, m, o2]);
+                } else {
+                    polygons.push(vec![v, o1, o2]);
+                }
+            }
+        }
+    }
+    // Caps
+    if !closed {
+        for &(p, d) in &[(points[0], scale(dir(0), -1.0)), (points[n - 1], dir(n - 2))] {
+            match style.cap {
+                LineCap::Butt => (),
+                LineCap::Round => polygons.push(circle_polygon(p, hw)),
+                LineCap::Square => {
+                    let nv = normal(d);
+                    let e = scale(d, hw);
+                    polygons.push(vec![add(p, nv), add(add(p, nv), e), add(sub(p, nv), e), sub(p, nv)]);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn thick_polyline() {
+        let mut pixels = vec![0; 64];
+        let mut fb = Framebuffer::new(8, 8, &mut pixels);
+        let style = StrokeStyle { width: 2.0, cap: LineCap::Square, ..Default::default() };
+        fb.draw_polyline(&[(2.0, 2.0), (6.0, 2.0), (6.0, 6.0)], false, &style, 1);
+        let expected = vec![
+            0, 0, 0, 0, 0, 0, 0, 0,
+            0, 1, 1, 1, 1, 1, 1, 0,
+            0, 1, 1, 1, 1, 1, 1, 0,
+            0, 0, 0, 0, 0, 1, 1, 0,
+            0, 0, 0, 0, 0, 1, 1, 0,
+            0, 0, 0, 0, 0, 1, 1, 0,
+            0, 0, 0, 0, 0, 1, 1, 0,
+            0, 0, 0, 0, 0, 0, 0, 0,
+        ];
+        assert_eq!(pixels, expected);
+    }
+
+    #[test]
+    fn dashes() {
+        let mut pixels = vec![0; 10];
+        let mut fb = Framebuffer::new(10, 1, &mut pixels);
+        let style = StrokeStyle { dashes: vec![2.0, 1.0], dash_offset: 1.0, ..Default::default() };
+        fb.draw_polyline(&[(0.0, 0.5), (10.0, 0.5)], false, &style, 1);
+        assert_eq!(pixels, vec![1, 0, 1, 1, 0, 1, 1, 0, 1, 1]);
+    }
+}
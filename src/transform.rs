@@ -0,0 +1,147 @@
+//! Affine transformations of bitmaps (rotation, scaling, shearing)
+
+use crate::{BlendMode, Bitmap, Filter, Framebuffer, Mask};
+
+/// 2x3 affine transformation matrix, mapping (x, y) to (a * x + c * y + tx, b * x + d * y + ty)
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Transform {
+    /// x scale / rotation factor of x
+    pub a: f32,
+    /// y shear / rotation factor of x
+    pub b: f32,
+    /// x shear / rotation factor of y
+    pub c: f32,
+    /// y scale / rotation factor of y
+    pub d: f32,
+    /// Horizontal translation
+    pub tx: f32,
+    /// Vertical translation
+    pub ty: f32,
+}
+
+impl Transform {
+    /// Identity transformation
+    pub fn identity() -> Transform {
+        Transform { a: 1.0, b: 0.0, c: 0.0, d: 1.0, tx: 0.0, ty: 0.0 }
+    }
+
+    /// Translation
+    pub fn translation(tx: f32, ty: f32) -> Transform {
+        Transform { tx, ty, ..Transform::identity() }
+    }
+
+    /// Scaling around the origin
+    pub fn scale(sx: f32, sy: f32) -> Transform {
+        Transform { a: sx, d: sy, ..Transform::identity() }
+    }
+
+    /// Rotation around the origin, angle in radians (clockwise on screen, y axis pointing down)
+    pub fn rotation(angle: f32) -> Transform {
+        let (sin, cos) = angle.sin_cos();
+        Transform { a: cos, b: sin, c: -sin, d: cos, tx: 0.0, ty: 0.0 }
+    }
+
+    /// Rotation around a pivot point, angle in radians
+    pub fn rotation_around(angle: f32, px: f32, py: f32) -> Transform {
+        Transform::translation(-px, -py)
+            .then(&Transform::rotation(angle))
+            .then(&Transform::translation(px, py))
+    }
+
+    /// Shearing along x (kx) and y (ky)
+    pub fn shear(kx: f32, ky: f32) -> Transform {
+        Transform { b: ky, c: kx, ..Transform::identity() }
+    }
+
+    /// Composition: applies self, then next
+    pub fn then(&self, next: &Transform) -> Transform {
+        Transform {
+            a: next.a * self.a + next.c * self.b,
+            b: next.b * self.a + next.d * self.b,
+            c: next.a * self.c + next.c * self.d,
+            d: next.b * self.c + next.d * self.d,
+            tx: next.a * self.tx + next.c * self.ty + next.tx,
+            ty: next.b * self.tx + next.d * self.ty + next.ty,
+        }
+    }
+
+    /// Transforms a point
+    pub fn apply(&self, x: f32, y: f32) -> (f32, f32) {
+        (self.a * x + self.c * y + self.tx, self.b * x + self.d * y + self.ty)
+    }
+
+    /// Inverse transformation, None if the matrix is singular
+    pub fn inverse(&self) -> Option<Transform> {
+        let det = self.a * self.d - self.b * self.c;
+        if det.abs() < f32::EPSILON {
+            return None;
+        }
+        let (a, b, c, d) = (self.d / det, -self.b / det, -self.c / det, self.a / det);
+        Some(Transform { a, b, c, d, tx: -(a * self.tx + c * self.ty), ty: -(b * self.tx + d * self.ty) })
+    }
+}
+
+impl Bitmap<'_> {
+    /// Draws the bitmap transformed by an affine transformation in bitmap space, then moved to the bitmap's coordinates
+    pub fn blit_transformed(&self, fb: &mut Framebuffer, t: &Transform, filter: Filter, mask: Mask) {
+        let inv = match t.inverse() {
+            Some(i) => i,
+            None => return
+        };
+        // Bounding box of the transformed bitmap
+        let corners = [t.apply(0.0, 0.0), t.apply(self.w as f32, 0.0), t.apply(0.0, self.h as f32), t.apply(self.w as f32, self.h as f32)];
+        // Clamped to the clip rectangle before conversion, as it may be far outside of the framebuffer
+        let (bx0, by0, bx1, by1) = fb.bounds();
+        let clamp = |v: f32, origin: isize, b0: usize, b1: usize| (v as f64 + origin as f64).clamp(b0 as f64, b1 as f64) as usize;
+        let x0 = clamp(corners.iter().fold(f32::MAX, |m, p| m.min(p.0)).floor(), self.x, bx0, bx1);
+        let y0 = clamp(corners.iter().fold(f32::MAX, |m, p| m.min(p.1)).floor(), self.y, by0, by1);
+        let x1 = clamp(corners.iter().fold(f32::MIN, |m, p| m.max(p.0)).ceil(), self.x, bx0, bx1);
+        let y1 = clamp(corners.iter().fold(f32::MIN, |m, p| m.max(p.1)).ceil(), self.y, by0, by1);
+        for fy in y0..y1 {
+            for fx in x0..x1 {
+                // Source coordinates of the destination pixel's center
+                let (u, v) = inv.apply((fx as f64 - self.x as f64) as f32 + 0.5, (fy as f64 - self.y as f64) as f32 + 0.5);
+                if u < 0.0 || v < 0.0 || u >= self.w as f32 || v >= self.h as f32 {
+                    continue;
+                }
+                let c = u as usize + v as usize * self.stride;
+                let src = match filter {
+                    Filter::Nearest => self.pixels[c],
+                    Filter::Bilinear => self.sample_bilinear(((u - 0.5) * 65536.0) as i64, ((v - 0.5) * 65536.0) as i64),
+                };
+                mask.write(&mut fb.pixels[fb.index(fx, fy)], src, c, BlendMode::Copy);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inverse() {
+        let t = Transform::rotation_around(0.7, 3.0, 2.0).then(&Transform::scale(2.0, 0.5)).then(&Transform::shear(0.3, 0.0));
+        let (x, y) = t.apply(5.0, -4.0);
+        let (u, v) = t.inverse().unwrap().apply(x, y);
+        assert!((u - 5.0).abs() < 1e-4 && (v + 4.0).abs() < 1e-4);
+        assert_eq!(Transform::scale(0.0, 1.0).inverse(), None);
+    }
+
+    #[test]
+    fn rotated_blit() {
+        let mut pixels = vec![0; 4];
+        let mut fb = Framebuffer::new(2, 2, &mut pixels);
+        let src = vec![1, 2, 3, 4];
+        let b = Bitmap::new(2, 2, 0, 0, &src);
+        b.blit_transformed(&mut fb, &Transform::rotation_around(std::f32::consts::FRAC_PI_2, 1.0, 1.0), Filter::Nearest, Mask::None);
+        assert_eq!(pixels, vec![3, 1, 4, 2]);
+        let mut fb = Framebuffer::new(2, 2, &mut pixels);
+        b.blit_transformed(&mut fb, &Transform::translation(-1.0, 0.0), Filter::Nearest, Mask::Color(4));
+        assert_eq!(pixels, vec![2, 1, 4, 2]);
+        // Bounding box beyond the range of isize, the source being shrunk to a point by the inverse
+        let mut fb = Framebuffer::new(2, 2, &mut pixels);
+        b.blit_transformed(&mut fb, &Transform::rotation(0.5).then(&Transform::scale(1e30, 1e30)), Filter::Nearest, Mask::None);
+        assert_eq!(pixels, vec![1; 4]);
+    }
+}
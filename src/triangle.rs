@@ -0,0 +1,189 @@
+//! Triangle rasterization, with flat or per-vertex colors, or texture mapping
+
+use crate::{Bitmap, Filter, Framebuffer};
+
+/// Triangle vertex
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub struct Vertex {
+    /// Horizontal position, pixel centers being at integer + 0.5 coordinates
+    pub x: f32,
+    /// Vertical position
+    pub y: f32,
+    /// Distance to the viewer (positive), used by perspective correct texture mapping
+    pub z: f32,
+    /// Color, interpolated by Gouraud shading
+    pub color: u32,
+    /// Horizontal texture coordinate, in texels
+    pub u: f32,
+    /// Vertical texture coordinate, in texels
+    pub v: f32,
+}
+
+impl Vertex {
+    /// Vertex at a position, at depth 1
+    pub fn new(x: f32, y: f32) -> Vertex {
+        Vertex { x, y, z: 1.0, ..Default::default() }
+    }
+}
+
+/// How the pixels of a triangle are colored
+#[derive(Clone, Copy)]
+pub enum Shading<'a> {
+    /// Single color
+    Flat(u32),
+    /// Vertex colors, interpolated
+    Gouraud,
+    /// Texels sampled from a bitmap, at the interpolated texture coordinates
+    Texture {
+        /// Texture
+        bitmap: &'a Bitmap<'a>,
+        /// Sampling filter
+        filter: Filter,
+        /// Perspective correct interpolation from the vertices' depths, affine otherwise
+        perspective: bool,
+    },
+}
+
+/// Twice the signed area of the (a, b, p) triangle
+fn edge(a: &Vertex, b: &Vertex, p: (f32, f32)) -> f32 {
+    (b.x - a.x) * (p.1 - a.y) - (b.y - a.y) * (p.0 - a.x)
+}
+
+/// Is the a to b edge a top or left edge, pixel centers exactly on it being inside (top-left rule)
+fn top_left(a: &Vertex, b: &Vertex) -> bool {
+    let (dx, dy) = (b.x - a.x, b.y - a.y);
+    dy < 0.0 || (dy == 0.0 && dx > 0.0)
+}
+
+/// Interpolation of three colors with barycentric weights, channel by channel
+fn interpolate_color(colors: [u32; 3], l: [f32; 3]) -> u32 {
+    (0..4).fold(0, |acc, i| {
+        let c = |color: u32| ((color >> (i * 8)) & 0xff) as f32;
+        let v = (c(colors[0]) * l[0] + c(colors[1]) * l[1] + c(colors[2]) * l[2]).round().clamp(0.0, 255.0) as u32;
+        acc | v << (i * 8)
+    })
+}
+
+impl Framebuffer<'_> {
+    /// Fills a triangle, clipped to the framebuffer. Pixels are inside if their center is, shared edges being drawn once.
+    pub fn fill_triangle(&mut self, vertices: &[Vertex; 3], shading: Shading) {
+        let [a, mut b, mut c] = *vertices;
+        let mut area = edge(&a, &b, (c.x, c.y));
+        if area == 0.0 || !area.is_finite() {
+            return;
+        }
+        if area < 0.0 {
+            std::mem::swap(&mut b, &mut c);
+            area = -area;
+        }
+        let v = [a, b, c];
+        let min_x = a.x.min(b.x).min(c.x);
+        let min_y = a.y.min(b.y).min(c.y);
+        let max_x = a.x.max(b.x).max(c.x);
+        let max_y = a.y.max(b.y).max(c.y);
+        let (bx0, by0, bx1, by1) = self.bounds();
+        let x0 = ((min_x - 0.5).ceil().max(bx0 as f32) as usize).min(bx1);
+        let y0 = ((min_y - 0.5).ceil().max(by0 as f32) as usize).min(by1);
+        let x1 = (((max_x - 0.5).floor() + 1.0).clamp(0.0, bx1 as f32) as usize).min(bx1);
+        let y1 = (((max_y - 0.5).floor() + 1.0).clamp(0.0, by1 as f32) as usize).min(by1);
+        // Edges opposite to each vertex
+        let edges = [(1, 2), (2, 0), (0, 1)];
+        let inclusive = edges.map(|(i, j)| top_left(&v[i], &v[j]));
+        let perspective = match shading {
+            Shading::Texture { bitmap, .. } if bitmap.w == 0 || bitmap.h == 0 => return,
+            Shading::Texture { perspective, .. } => perspective && v.iter().all(|v| v.z > 0.0),
+            _ => false
+        };
+        for y in y0..y1 {
+            for x in x0..x1 {
+                let p = (x as f32 + 0.5, y as f32 + 0.5);
+                let w = edges.map(|(i, j)| edge(&v[i], &v[j], p));
+                if (0..3).any(|i| w[i] < 0.0 || (w[i] == 0.0 && !inclusive[i])) {
+                    continue;
+                }
+                let mut l = w.map(|w| w / area);
+                let color = match shading {
+                    Shading::Flat(color) => color,
+                    Shading::Gouraud => interpolate_color([a.color, b.color, c.color], l),
+                    Shading::Texture { bitmap, filter, .. } => {
+                        if perspective {
+                            // Weights interpolated linearly in 1 / z
+                            let q = [l[0] / v[0].z, l[1] / v[1].z, l[2] / v[2].z];
+                            let s = q[0] + q[1] + q[2];
+                            l = q.map(|q| q / s);
+                        }
+                        let u = v[0].u * l[0] + v[1].u * l[1] + v[2].u * l[2];
+                        let tv = v[0].v * l[0] + v[1].v * l[1] + v[2].v * l[2];
+                        match filter {
+                            Filter::Nearest => {
+                                let tx = (u.floor().max(0.0) as usize).min(bitmap.w - 1);
+                                let ty = (tv.floor().max(0.0) as usize).min(bitmap.h - 1);
+                                bitmap.pixels[tx + ty * bitmap.stride]
+                            }
+                            Filter::Bilinear => bitmap.sample_bilinear(((u - 0.5) * 65536.0) as i64, ((tv - 0.5) * 65536.0) as i64),
+                        }
+                    }
+                };
+                self.pixels[self.index(x, y)] = color;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shared_edges() {
+        // Two triangles covering a square: every pixel is drawn exactly once
+        let corners = [Vertex::new(0.0, 0.0), Vertex::new(4.0, 0.0), Vertex::new(4.0, 4.0), Vertex::new(0.0, 4.0)];
+        let mut first = vec![0; 16];
+        Framebuffer::new(4, 4, &mut first).fill_triangle(&[corners[0], corners[1], corners[2]], Shading::Flat(1));
+        let mut second = vec![0; 16];
+        Framebuffer::new(4, 4, &mut second).fill_triangle(&[corners[2], corners[3], corners[0]], Shading::Flat(1));
+        assert!(first.iter().zip(second.iter()).all(|(a, b)| a + b == 1));
+        assert_eq!(first.iter().sum::<u32>(), 10);
+        // Bounding box far beyond the framebuffer
+        let mut pixels = vec![0; 16];
+        Framebuffer::new(4, 4, &mut pixels).fill_triangle(&[corners[0], Vertex::new(2e19, 0.0), corners[3]], Shading::Flat(1));
+        assert_eq!(pixels, vec![1; 16]);
+    }
+
+    #[test]
+    fn gouraud() {
+        let mut pixels = vec![0; 4];
+        let mut fb = Framebuffer::new(4, 1, &mut pixels);
+        let a = Vertex { color: 0x000000, ..Vertex::new(0.0, 0.5) };
+        let b = Vertex { color: 0x0000ff, ..Vertex::new(4.0, -1000.0) };
+        let c = Vertex { color: 0x0000ff, ..Vertex::new(4.0, 1000.0) };
+        fb.fill_triangle(&[a, b, c], Shading::Gouraud);
+        assert_eq!(pixels, vec![0x20, 0x60, 0x9f, 0xdf]);
+    }
+
+    #[test]
+    fn texture() {
+        let texture = Bitmap::new(2, 2, 0, 0, &[1, 2, 3, 4]);
+        let shading = Shading::Texture { bitmap: &texture, filter: Filter::Nearest, perspective: true };
+        let corners = [
+            Vertex { u: 0.0, v: 0.0, ..Vertex::new(0.0, 0.0) },
+            Vertex { u: 2.0, v: 0.0, ..Vertex::new(4.0, 0.0) },
+            Vertex { u: 2.0, v: 2.0, ..Vertex::new(4.0, 4.0) },
+            Vertex { u: 0.0, v: 2.0, ..Vertex::new(0.0, 4.0) },
+        ];
+        let mut pixels = vec![0; 16];
+        let mut fb = Framebuffer::new(4, 4, &mut pixels);
+        fb.fill_triangle(&[corners[0], corners[1], corners[2]], shading);
+        fb.fill_triangle(&[corners[2], corners[3], corners[0]], shading);
+        assert_eq!(pixels, vec![1, 1, 2, 2, 1, 1, 2, 2, 3, 3, 4, 4, 3, 3, 4, 4]);
+        // Farther right side: its texels are compressed
+        let mut corners = corners;
+        corners[1].z = 3.0;
+        corners[2].z = 3.0;
+        let mut pixels = vec![0; 16];
+        let mut fb = Framebuffer::new(4, 4, &mut pixels);
+        fb.fill_triangle(&[corners[0], corners[1], corners[2]], shading);
+        fb.fill_triangle(&[corners[2], corners[3], corners[0]], shading);
+        assert_eq!(&pixels[0..4], &[1, 1, 1, 2]);
+    }
+}
@@ -0,0 +1,191 @@
+//! TrueType / OpenType fonts, rasterized with anti-aliasing into a glyph cache (truetype feature)
+
+use crate::{FontError, Framebuffer, TextFont};
+use ab_glyph::{point, Font, FontVec, GlyphId, PxScale, ScaleFont};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+/// Rasterized glyph. Its pixels are white with the coverage as alpha (0xffffffAA), to be used with `Bitmap::new`
+/// and `Mask::Alpha`.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct GlyphImage {
+    /// Width in pixels
+    pub w: usize,
+    /// Height in pixels
+    pub h: usize,
+    /// Horizontal offset from the glyph's origin
+    pub x: isize,
+    /// Vertical offset from the glyph's origin, on the baseline
+    pub y: isize,
+    /// RGBA pixels
+    pub pixels: Vec<u32>,
+}
+
+/// Scalable font at a pixel size, with a cache of its rasterized glyphs
+pub struct TrueTypeFont {
+    font: FontVec,
+    scale: PxScale,
+    cache: RefCell<HashMap<GlyphId, Rc<GlyphImage>>>,
+}
+
+impl TrueTypeFont {
+    /// Parses a TrueType or OpenType font, to be rendered at a size in pixels (line height)
+    pub fn from_bytes(data: Vec<u8>, size: f32) -> Result<TrueTypeFont, FontError> {
+        let font = FontVec::try_from_vec(data).map_err(|_| FontError::UnknownFormat)?;
+        Ok(TrueTypeFont { font, scale: PxScale::from(size), cache: RefCell::new(HashMap::new()) })
+    }
+
+    /// Distance from the top of a line to the baseline
+    pub fn baseline(&self) -> isize {
+        self.font.as_scaled(self.scale).ascent().ceil() as isize
+    }
+
+    /// Rasterized glyph of a character, from the cache
+    pub fn glyph(&self, c: char) -> Rc<GlyphImage> {
+        self.glyph_image(self.font.glyph_id(c))
+    }
+
+    fn glyph_image(&self, id: GlyphId) -> Rc<GlyphImage> {
+        if let Some(image) = self.cache.borrow().get(&id) {
+            return image.clone();
+        }
+        let image = match self.font.outline_glyph(id.with_scale_and_position(self.scale, point(0.0, 0.0))) {
+            Some(outline) => {
+                let bounds = outline.px_bounds();
+                let (w, h) = (bounds.width() as usize, bounds.height() as usize);
+                let mut pixels = vec![0xffff_ff00; w * h];
+                outline.draw(|x, y, c| {
+                    let (x, y) = (x as usize, y as usize);
+                    if x < w && y < h {
+                        pixels[x + y * w] = 0xffff_ff00 | (c * 255.0).round().clamp(0.0, 255.0) as u32;
+                    }
+                });
+                GlyphImage { w, h, x: bounds.min.x as isize, y: bounds.min.y as isize, pixels }
+            }
+            None => GlyphImage { w: 0, h: 0, x: 0, y: 0, pixels: Vec::new() }
+        };
+        let image = Rc::new(image);
+        self.cache.borrow_mut().insert(id, image.clone());
+        image
+    }
+
+    /// Calls a function with the glyph and the rounded origin of each character of a line
+    fn layout<F: FnMut(GlyphId, isize)>(&self, text: &str, mut f: F) -> f32 {
+        let font = self.font.as_scaled(self.scale);
+        let mut x = 0.0;
+        let mut previous = None;
+        for c in text.chars() {
+            let id = font.glyph_id(c);
+            if let Some(p) = previous {
+                x += font.kern(p, id);
+            }
+            f(id, x.round() as isize);
+            x += font.h_advance(id);
+            previous = Some(id);
+        }
+        x
+    }
+
+    /// Draws a line of text with its top left corner at (x, y), blending the glyphs' coverage with a color,
+    /// clipped to the framebuffer
+    pub fn draw_text(&self, fb: &mut Framebuffer, x: isize, y: isize, text: &str, color: u32) {
+        let baseline = y + self.baseline();
+        self.layout(text, |id, gx| {
+            let g = self.glyph_image(id);
+            let (ox, oy) = (x + gx + g.x, baseline + g.y);
+            let (x0, y0, x1, y1) = match fb.clip(ox, oy, g.w, g.h) {
+                Some(r) => r,
+                None => return
+            };
+            for fy in y0..y1 {
+                for fx in x0..x1 {
+                    let coverage = g.pixels[(fx as isize - ox) as usize + (fy as isize - oy) as usize * g.w] & 0xff;
+                    fb.blend_pixel(fx as isize, fy as isize, color, coverage);
+                }
+            }
+        });
+    }
+}
+
+impl TextFont for TrueTypeFont {
+    fn line_height(&self) -> usize {
+        self.font.as_scaled(self.scale).height().ceil() as usize
+    }
+
+    fn text_width(&self, text: &str) -> isize {
+        self.layout(text, |_, _| ()).ceil() as isize
+    }
+
+    fn kerning(&self, left: char, right: char) -> isize {
+        let font = self.font.as_scaled(self.scale);
+        font.kern(font.glyph_id(left), font.glyph_id(right)).ceil() as isize
+    }
+
+    fn draw_str(&self, fb: &mut Framebuffer, x: isize, y: isize, text: &str, color: u32) {
+        self.draw_text(fb, x, y, text, color);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Big endian bytes of 16 bits values
+    fn be16(values: &[i32]) -> Vec<u8> {
+        values.iter().flat_map(|v| (*v as u16).to_be_bytes()).collect()
+    }
+
+    /// Minimal TrueType font, 1000 units per em: glyph 1 ('A') is a 500 units square on the baseline
+    fn square_font() -> Vec<u8> {
+        let mut head = be16(&[1, 0, 0, 0, 0, 0, 0x5f0f, 0x3cf5, 0, 1000]);
+        head.extend(vec![0; 16]);
+        head.extend(be16(&[0, 0, 500, 500, 0, 8, 2, 0, 0]));
+        let hhea = be16(&[1, 0, 800, -200, 0, 600, 0, 0, 500, 1, 0, 0, 0, 0, 0, 0, 0, 2]);
+        let maxp = be16(&[0, 0x5000, 2]);
+        let hmtx = be16(&[600, 0, 600, 0]);
+        // Format 4 cmap: 'A' to glyph 1
+        let mut cmap = be16(&[0, 1, 3, 1, 0, 12]);
+        cmap.extend(be16(&[4, 32, 0, 4, 4, 1, 0, 0x41, 0xffff, 0, 0x41, 0xffff, -0x40, 1, 0, 0]));
+        let mut glyf = be16(&[1, 0, 0, 500, 500, 3]);
+        glyf.extend(be16(&[0]));
+        glyf.extend([1u8, 1, 1, 1]);
+        glyf.extend(be16(&[0, 0, 500, 0, 0, 500, 0, -500]));
+        let loca = be16(&[0, 0, glyf.len() as i32 / 2]);
+        let tables: [(&[u8; 4], Vec<u8>); 7] =
+            [(b"cmap", cmap), (b"glyf", glyf), (b"head", head), (b"hhea", hhea), (b"hmtx", hmtx), (b"loca", loca), (b"maxp", maxp)];
+        let mut font = vec![0, 1, 0, 0, 0, 7, 0, 64, 0, 2, 0, 48];
+        let mut offset = 12 + 16 * tables.len();
+        let mut data = Vec::new();
+        for (tag, table) in &tables {
+            font.extend_from_slice(*tag);
+            font.extend([0; 4]);
+            font.extend((offset as u32).to_be_bytes());
+            font.extend((table.len() as u32).to_be_bytes());
+            let mut table = table.clone();
+            table.resize(table.len().div_ceil(4) * 4, 0);
+            offset += table.len();
+            data.extend(table);
+        }
+        font.extend(data);
+        font
+    }
+
+    #[test]
+    fn rasterized_text() {
+        let font = TrueTypeFont::from_bytes(square_font(), 10.0).unwrap();
+        assert_eq!(font.baseline(), 8);
+        assert_eq!(font.line_height(), 10);
+        assert_eq!(font.text_width("AA"), 12);
+        let g = font.glyph('A');
+        assert_eq!((g.w, g.h, g.x, g.y), (5, 5, 0, -5));
+        assert!(g.pixels.iter().all(|p| *p == 0xffff_ffff));
+        assert!(Rc::ptr_eq(&g, &font.glyph('A')));
+        let mut pixels = vec![0; 12 * 10];
+        let mut fb = Framebuffer::new(12, 10, &mut pixels);
+        font.draw_text(&mut fb, 0, 0, "AA", 0xff);
+        assert_eq!(&pixels[3 * 12..4 * 12], &[0xff, 0xff, 0xff, 0xff, 0xff, 0, 0xff, 0xff, 0xff, 0xff, 0xff, 0]);
+        assert_eq!(pixels[8 * 12], 0);
+        assert!(TrueTypeFont::from_bytes(vec![0; 16], 10.0).is_err());
+    }
+}